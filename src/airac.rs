@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use std::fmt::Display;
+
+/// Number of days between two AIRAC effective dates
+pub const CYCLE_DAYS: i64 = 28;
+
+/// Effective date of cycle 2401, from which all other cycles are derived
+fn reference_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 25).expect("valid reference date")
+}
+
+/// An AIRAC cycle, identified by the year and its number within the year (e.g. 2611)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AiracCycle {
+    /// Date the cycle becomes effective
+    pub effective: NaiveDate,
+}
+
+impl AiracCycle {
+    /// The cycle in effect on `date`
+    pub fn at(date: NaiveDate) -> Self {
+        let cycles = (date - reference_date()).num_days().div_euclid(CYCLE_DAYS);
+        Self {
+            effective: reference_date() + Duration::days(cycles * CYCLE_DAYS),
+        }
+    }
+
+    /// The cycle in effect today
+    ///
+    /// Cycles become effective at 00:00 UTC, so the current date is taken in UTC.
+    pub fn current() -> Self {
+        Self::at(Utc::now().date_naive())
+    }
+
+    /// The cycle following this one
+    pub fn next(&self) -> Self {
+        Self {
+            effective: self.effective + Duration::days(CYCLE_DAYS),
+        }
+    }
+
+    /// Last day of the cycle
+    pub fn end(&self) -> NaiveDate {
+        self.next().effective - Duration::days(1)
+    }
+
+    /// Number of the cycle within its year, starting at 1
+    pub fn number(&self) -> u32 {
+        // Cycles never start more than 28 days into the year
+        (self.effective.ordinal0() / CYCLE_DAYS as u32) + 1
+    }
+
+    /// Number of days from `date` until this cycle becomes effective
+    /// (negative once it is effective)
+    pub fn days_until(&self, date: NaiveDate) -> i64 {
+        (self.effective - date).num_days()
+    }
+
+    /// Cycle identifier, e.g. "2611"
+    pub fn ident(&self) -> String {
+        format!("{:02}{:02}", self.effective.year() % 100, self.number())
+    }
+}
+
+impl Display for AiracCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AIRAC {} ({})",
+            self.ident(),
+            self.effective.format("%d %b %Y")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn cycle_in_effect() {
+        assert_eq!(AiracCycle::at(date(2024, 1, 25)).ident(), "2401");
+        assert_eq!(AiracCycle::at(date(2025, 1, 22)).ident(), "2413");
+        assert_eq!(AiracCycle::at(date(2025, 1, 23)).ident(), "2501");
+        assert_eq!(AiracCycle::at(date(2023, 12, 31)).ident(), "2313");
+    }
+
+    #[test]
+    fn next_and_end() {
+        let cycle = AiracCycle::at(date(2025, 1, 23));
+        assert_eq!(cycle.next().effective, date(2025, 2, 20));
+        assert_eq!(cycle.end(), date(2025, 2, 19));
+        assert_eq!(cycle.days_until(date(2025, 1, 20)), 3);
+        assert_eq!(cycle.to_string(), "AIRAC 2501 (23 Jan 2025)");
+    }
+}
@@ -19,17 +19,226 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
-use crate::config::Config;
-use crate::models::{OperationStatus, VacEntryWithSelection};
+use chrono::Utc;
 use eframe::egui;
 use egui_extras::{Column, TableBuilder};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use vac_downloader_gui::airac::AiracCycle;
+use vac_downloader_gui::archive;
+use vac_downloader_gui::collections::{ChartView, Collections};
+use vac_downloader_gui::compare::{ChartComparison, DiffLine, EditionComparison, PageDiff};
+use vac_downloader_gui::config::{Config, SyncSchedule};
+use vac_downloader_gui::core::{Command, Core, SortColumn, StatusFilter};
+use vac_downloader_gui::history::ChartHistory;
+use vac_downloader_gui::library::{ForeignFile, RelocationMode, VerifyReport};
+use vac_downloader_gui::mirror::MirrorPlan;
+use vac_downloader_gui::models::{DownloadReport, LocalChart, OperationStatus, format_bytes};
+use vac_downloader_gui::route::Route;
+use vac_downloader_gui::trash::Trash;
+use vac_downloader_gui::ui_state::UiState;
+
+/// Comparison of a previous edition of a chart with the current one
+struct ComparisonView {
+    oaci: String,
+    version: String,
+    result: Result<ChartComparison, String>,
+    show_unchanged: bool,
+    /// Show the text changes instead of the drawing
+    show_text: bool,
+    /// Index of the page shown
+    page: usize,
+    /// Scale of the page image
+    zoom: f32,
+    /// Page images, uploaded when first shown
+    textures: HashMap<usize, egui::TextureHandle>,
+}
+
+impl ComparisonView {
+    fn new(comparison: EditionComparison) -> Self {
+        Self {
+            oaci: comparison.oaci,
+            version: comparison.version,
+            result: comparison.result,
+            show_unchanged: false,
+            show_text: false,
+            page: 0,
+            zoom: 1.0,
+            textures: HashMap::new(),
+        }
+    }
+
+    fn page_texture(
+        textures: &mut HashMap<usize, egui::TextureHandle>,
+        ctx: &egui::Context,
+        index: usize,
+        page: &PageDiff,
+    ) -> egui::TextureHandle {
+        textures
+            .entry(index)
+            .or_insert_with(|| {
+                let image = egui::ColorImage::from_rgba_unmultiplied(
+                    [page.width as usize, page.height as usize],
+                    &page.pixels,
+                );
+                ctx.load_texture(
+                    format!("comparison-page-{}", index),
+                    image,
+                    egui::TextureOptions::LINEAR,
+                )
+            })
+            .clone()
+    }
+
+    fn show_drawing_changes(&mut self, ui: &mut egui::Ui) {
+        let Ok(comparison) = &self.result else {
+            return;
+        };
+        let pages = match &comparison.pages {
+            Ok(pages) if !pages.is_empty() => pages,
+            Ok(_) => {
+                ui.label("Neither edition has any page.");
+                return;
+            }
+            Err(e) => {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!("The pages could not be drawn: {}", e),
+                );
+                ui.label("Changes to the drawing are not shown; see the Text tab.");
+                return;
+            }
+        };
+        self.page = self.page.min(pages.len() - 1);
+        let changed_pages = pages.iter().filter(|p| !p.areas.is_empty()).count();
+        let incomplete_pages = pages.iter().filter(|p| !p.skipped.is_empty()).count();
+
+        ui.label(format!(
+            "{} of {} page(s) changed ({} page(s) before, {} now)",
+            changed_pages,
+            pages.len(),
+            comparison.old_pages,
+            comparison.new_pages
+        ));
+        if incomplete_pages > 0 {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 140, 0),
+                format!(
+                    "⚠ {} page(s) have content that could not be drawn, changes to it are not shown",
+                    incomplete_pages
+                ),
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.colored_label(egui::Color32::from_rgb(220, 40, 40), "Removed");
+            ui.colored_label(egui::Color32::from_rgb(40, 170, 40), "Added");
+            ui.colored_label(egui::Color32::from_rgb(255, 140, 0), "Changed area");
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.page > 0, egui::Button::new("◀"))
+                .clicked()
+            {
+                self.page -= 1;
+            }
+            ui.label(format!("Page {} of {}", self.page + 1, pages.len()));
+            if ui
+                .add_enabled(self.page + 1 < pages.len(), egui::Button::new("▶"))
+                .clicked()
+            {
+                self.page += 1;
+            }
+            ui.separator();
+            ui.add(egui::Slider::new(&mut self.zoom, 0.25..=2.0).text("Zoom"));
+        });
+
+        let page = &pages[self.page];
+        for skipped in &page.skipped {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 140, 0),
+                format!("⚠ Not drawn: {}", skipped),
+            );
+        }
+        if page.areas.is_empty() && page.skipped.is_empty() {
+            ui.label("No change on this page.");
+        } else if page.areas.is_empty() {
+            ui.label("No change in the content drawn on this page.");
+        } else {
+            ui.label(format!(
+                "{} changed area(s), {} pixel(s)",
+                page.areas.len(),
+                page.changed_pixels
+            ));
+        }
+        ui.separator();
+
+        let texture = Self::page_texture(&mut self.textures, ui.ctx(), self.page, page);
+        let size = egui::vec2(page.width as f32, page.height as f32) * self.zoom
+            / ui.ctx().pixels_per_point();
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.image((texture.id(), size));
+        });
+    }
+
+    fn show_text_changes(&mut self, ui: &mut egui::Ui) {
+        let Ok(comparison) = &self.result else {
+            return;
+        };
+        let (removed, added) = comparison.changes();
+        ui.label(format!(
+            "{} line(s) removed, {} line(s) added ({} page(s) before, {} now)",
+            removed, added, comparison.old_pages, comparison.new_pages
+        ));
+        if comparison.pages.is_err() {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 140, 0),
+                "⚠ The pages could not be drawn: only the text is compared",
+            );
+        }
+        ui.checkbox(&mut self.show_unchanged, "Show unchanged lines");
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if removed + added == 0 {
+                ui.label("The text of both editions is identical.");
+            }
+            for line in &comparison.lines {
+                match line {
+                    DiffLine::Same(text) if self.show_unchanged => {
+                        ui.monospace(format!("  {}", text));
+                    }
+                    DiffLine::Same(_) => {}
+                    DiffLine::Removed(text) => {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(220, 60, 60),
+                            egui::RichText::new(format!("- {}", text)).monospace(),
+                        );
+                    }
+                    DiffLine::Added(text) => {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(60, 170, 60),
+                            egui::RichText::new(format!("+ {}", text)).monospace(),
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum SortColumn {
-    Oaci,
-    City,
+/// Files of the download directory unknown to the cache
+struct ForeignFilesDialog {
+    /// Each file, whether it is selected and the OACI code to adopt it as
+    files: Vec<(ForeignFile, bool, String)>,
+    /// The user asked to delete the selected files and must confirm
+    confirm_delete: bool,
+}
+
+/// Deleted charts shown in the trash window
+struct TrashDialog {
+    trash: Trash,
+    /// Trash items the user asked to delete for good and must confirm
+    confirm_purge: Option<Vec<String>>,
 }
 
 /// Icon storage for the application
@@ -47,33 +256,65 @@ struct Icons {
     status_update: egui::TextureHandle,
 }
 
+/// Briefing pack being prepared in the export dialog
+struct BriefingDialog {
+    /// OACI codes of the charts, in booklet order
+    codes: Vec<String>,
+    /// Start the booklet with a cover page
+    cover: bool,
+}
+
 pub struct VacDownloaderApp {
-    /// List of VAC entries
-    vac_entries: Arc<Mutex<Vec<VacEntryWithSelection>>>,
-    /// Current operation status
-    status: Arc<Mutex<OperationStatus>>,
-    /// Shared VacDownloader instance (benefits from caching)
-    downloader: Arc<Mutex<vac_downloader::VacDownloader>>,
-    /// Application configuration
-    config: Config,
+    /// Chart management service
+    core: Core,
     /// Editable download directory path (for UI input)
     download_dir_input: String,
+    /// Show the relocation dialog (new download directory)
+    relocation_prompt: Option<String>,
+    /// Show the mirror targets window
+    show_mirrors: bool,
+    /// Changes planned for each mirror target, shown in the mirror window
+    mirror_preview: Option<Vec<MirrorPlan>>,
+    /// Show the briefing pack export dialog
+    briefing: Option<BriefingDialog>,
+    /// Show the archive export dialog (OACI codes of the local charts to export)
+    archive_export: Option<Vec<String>>,
+    /// Show the previous editions of a chart
+    history: Option<ChartHistory>,
+    /// Show the foreign files found in the download directory
+    foreign_files: Option<ForeignFilesDialog>,
+    /// Show the comparison of a previous edition with the current chart
+    comparison: Option<ComparisonView>,
     /// Show delete confirmation dialog (list of OACI codes to delete)
     delete_confirmation: Option<Vec<String>>,
-    /// Current sort column
-    sort_column: SortColumn,
-    /// Sort ascending or descending
-    sort_ascending: bool,
+    /// Trash items of the last deletion and when it happened, for the undo toast
+    undo: Option<(Vec<String>, Instant)>,
+    /// Show the trash window
+    trash: Option<TrashDialog>,
     /// Search query for filtering VAC list
     search_query: String,
-    /// Cache of needs_update status for each OACI code
-    needs_update_cache: Arc<Mutex<std::collections::HashMap<String, bool>>>,
+    /// Local status filter for the VAC list
+    status_filter: StatusFilter,
+    /// Route typed or pasted by the user (OACI codes or flight plan text)
+    route_input: String,
+    /// Favorite charts and named collections
+    collections: Collections,
+    /// Favorites or collection shown in the chart table
+    view: ChartView,
+    /// Name typed for a new collection
+    new_collection_name: String,
+    /// Collection being renamed and its edited name
+    renaming: Option<(String, String)>,
+    /// Sync and history settings being edited, not saved yet
+    sync_draft: Option<Config>,
+    /// User interface state remembered across sessions
+    ui_state: UiState,
     /// Application icons
     icons: Icons,
 }
 
 impl VacDownloaderApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, ui_state: UiState) -> Self {
         // Customize egui style
         let mut style = (*cc.egui_ctx.style()).clone();
         style.spacing.item_spacing = egui::vec2(8.0, 8.0);
@@ -87,27 +328,53 @@ impl VacDownloaderApp {
         // Load icons
         let icons = Self::load_icons(&cc.egui_ctx);
 
-        // Initialize VacDownloader with config paths
-        let downloader =
-            vac_downloader::VacDownloader::new(&config.database_path, &config.download_directory)
-                .expect("Failed to initialize VacDownloader");
+        // Initialize the core service with config paths
+        let core = Core::new(&config).expect("Failed to initialize VacDownloader");
+
+        // Restore the sort order and selection of the previous session
+        core.set_sort(ui_state.sort_column, ui_state.sort_ascending);
+        core.set_selection(&ui_state.selected);
+        core.state().last_sync = ui_state.last_sync;
+
+        let collections = Collections::load();
+        let view = match &ui_state.view {
+            ChartView::Collection(name) if collections.get(name).is_none() => ChartView::All,
+            view => view.clone(),
+        };
 
         let app = Self {
-            vac_entries: Arc::new(Mutex::new(Vec::new())),
-            status: Arc::new(Mutex::new(OperationStatus::Idle)),
-            downloader: Arc::new(Mutex::new(downloader)),
+            core,
             download_dir_input: config.download_directory.clone(),
-            config,
+            relocation_prompt: None,
+            show_mirrors: false,
+            mirror_preview: None,
+            briefing: None,
+            archive_export: None,
+            history: None,
+            foreign_files: None,
+            comparison: None,
             delete_confirmation: None,
-            sort_column: SortColumn::Oaci,
-            sort_ascending: true,
-            search_query: String::new(),
-            needs_update_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            undo: None,
+            sync_draft: None,
+            trash: None,
+            search_query: ui_state.search_query.clone(),
+            status_filter: ui_state.status_filter,
+            route_input: ui_state.route.clone(),
+            collections,
+            view,
+            new_collection_name: String::new(),
+            renaming: None,
+            ui_state,
             icons,
         };
 
-        // Fetch the VAC list on startup
-        app.fetch_vac_list();
+        if let Err(e) = app.core.purge_expired_trash() {
+            eprintln!("Failed to empty the trash: {}", e);
+        }
+
+        // Fetch the VAC list on startup, then keep it up to date if configured
+        app.core.execute(Command::FetchList);
+        app.core.start_scheduler();
 
         app
     }
@@ -158,325 +425,1382 @@ impl VacDownloaderApp {
         }
     }
 
-    fn fetch_vac_list(&self) {
-        let vac_entries = self.vac_entries.clone();
-        let status = self.status.clone();
-        let downloader = self.downloader.clone();
-        let sort_column = self.sort_column;
-        let sort_ascending = self.sort_ascending;
+    /// Sort the chart list and remember the sort order for the next session
+    fn set_sort(&mut self, column: SortColumn, ascending: bool) {
+        self.core.set_sort(column, ascending);
+        self.ui_state.sort_column = column;
+        self.ui_state.sort_ascending = ascending;
+        self.save_ui_state();
+    }
+
+    /// Save the current UI state, including the selection and window geometry
+    fn save_ui_state(&mut self) {
+        self.ui_state.search_query = self.search_query.clone();
+        self.ui_state.status_filter = self.status_filter;
+        self.ui_state.route = self.route_input.clone();
+        self.ui_state.view = self.view.clone();
+        self.ui_state.selected = self.core.state().selection_to_save();
+        self.ui_state.last_sync = self.core.state().last_sync;
+        if let Err(e) = self.ui_state.save() {
+            eprintln!("Failed to save UI state: {}", e);
+        }
+    }
+
+    fn save_collections(&self) {
+        if let Err(e) = self.collections.save() {
+            self.core.set_status(OperationStatus::Error(format!(
+                "Failed to save collections: {}",
+                e
+            )));
+        }
+    }
+
+    /// Sidebar listing the favorites and collections, with actions on the active one
+    fn show_collections_panel(&mut self, ctx: &egui::Context) {
+        let is_busy = self.core.is_busy();
+        let mut changed = false;
+
+        egui::SidePanel::left("collections_panel")
+            .resizable(true)
+            .default_width(200.0)
+            .show(ctx, |ui| {
+                ui.heading("Collections");
+                ui.separator();
+
+                ui.selectable_value(&mut self.view, ChartView::All, "All charts");
+                ui.selectable_value(
+                    &mut self.view,
+                    ChartView::Favorites,
+                    format!("★ Favorites ({})", self.collections.favorites.len()),
+                );
+
+                let mut remove: Option<String> = None;
+                let mut rename: Option<(String, String)> = None;
+                let mut cancel_rename = false;
+                for collection in &self.collections.collections {
+                    if let Some((name, new_name)) = &mut self.renaming
+                        && *name == collection.name
+                    {
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(new_name);
+                            if ui.button("OK").clicked() {
+                                rename = Some((name.clone(), new_name.clone()));
+                            }
+                            if ui.button("Cancel").clicked() {
+                                cancel_rename = true;
+                            }
+                        });
+                        continue;
+                    }
+
+                    let view = ChartView::Collection(collection.name.clone());
+                    let label = format!("{} ({})", collection.name, collection.codes.len());
+                    ui.selectable_value(&mut self.view, view, label)
+                        .context_menu(|ui| {
+                            if ui.button("Rename").clicked() {
+                                self.renaming =
+                                    Some((collection.name.clone(), collection.name.clone()));
+                                ui.close();
+                            }
+                            if ui.button("Delete").clicked() {
+                                remove = Some(collection.name.clone());
+                                ui.close();
+                            }
+                        });
+                }
 
-        *status.lock().unwrap() = OperationStatus::FetchingList;
+                if cancel_rename {
+                    self.renaming = None;
+                }
+                if let Some((name, new_name)) = rename {
+                    match self.collections.rename(&name, &new_name) {
+                        Ok(()) => {
+                            if self.view == ChartView::Collection(name) {
+                                self.view = ChartView::Collection(new_name.trim().to_string());
+                            }
+                            self.renaming = None;
+                            changed = true;
+                        }
+                        Err(e) => self.core.set_status(OperationStatus::Error(e)),
+                    }
+                }
+                if let Some(name) = remove {
+                    if self.view == ChartView::Collection(name.clone()) {
+                        self.view = ChartView::All;
+                    }
+                    self.collections.remove(&name);
+                    changed = true;
+                }
+
+                if ui
+                    .selectable_label(self.trash.is_some(), "🗑 Trash")
+                    .on_hover_text("Restore or permanently delete the deleted charts")
+                    .clicked()
+                {
+                    self.open_trash();
+                }
 
-        thread::spawn(move || {
-            let downloader = downloader.lock().unwrap();
-            match downloader.list_vacs(None) {
-                Ok(vacs) => {
-                    let mut entries: Vec<VacEntryWithSelection> =
-                        vacs.into_iter().map(VacEntryWithSelection::new).collect();
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_collection_name)
+                            .hint_text("New collection")
+                            .desired_width(120.0),
+                    );
+                    if ui.button("Create").clicked() {
+                        match self.collections.create(&self.new_collection_name) {
+                            Ok(()) => {
+                                self.view = ChartView::Collection(
+                                    self.new_collection_name.trim().to_string(),
+                                );
+                                self.new_collection_name.clear();
+                                changed = true;
+                            }
+                            Err(e) => self.core.set_status(OperationStatus::Error(e)),
+                        }
+                    }
+                });
 
-                    // Apply default sorting
-                    Self::sort_entries_static(&mut entries, sort_column, sort_ascending);
+                // Actions on the favorites or collection shown
+                let Some(codes) = self.collections.codes(&self.view).map(|c| c.to_vec()) else {
+                    return;
+                };
+                ui.separator();
+                let selected = self.core.state().selected_codes();
 
-                    *vac_entries.lock().unwrap() = entries;
-                    *status.lock().unwrap() = OperationStatus::Idle;
+                if ui
+                    .add_enabled(!selected.is_empty(), egui::Button::new("Add Selected"))
+                    .clicked()
+                {
+                    self.collections.add_charts(&self.view, &selected);
+                    changed = true;
+                }
+                if ui
+                    .add_enabled(!selected.is_empty(), egui::Button::new("Remove Selected"))
+                    .clicked()
+                {
+                    self.collections.remove_charts(&self.view, &selected);
+                    changed = true;
+                }
+                if ui
+                    .add_enabled(
+                        !is_busy && !codes.is_empty(),
+                        egui::Button::image_and_text(
+                            egui::Image::new(&self.icons.download)
+                                .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                            "Download / Update",
+                        ),
+                    )
+                    .on_hover_text("Download the missing charts and update the outdated ones")
+                    .clicked()
+                {
+                    self.core.execute(Command::Download(Some(codes.clone())));
                 }
-                Err(e) => {
-                    *status.lock().unwrap() =
-                        OperationStatus::Error(format!("Failed to fetch list: {}", e));
+                if ui
+                    .add_enabled(
+                        !is_busy && !codes.is_empty(),
+                        egui::Button::new("Export Briefing"),
+                    )
+                    .on_hover_text("Merge the local charts into a single PDF")
+                    .clicked()
+                {
+                    self.open_briefing(codes);
                 }
+            });
+
+        if changed {
+            self.save_collections();
+        }
+    }
+
+    /// Storage box: disk usage of the local charts and free space
+    fn show_storage(&mut self, ui: &mut egui::Ui) {
+        let storage = self.core.state().storage.clone();
+
+        egui::CollapsingHeader::new(format!(
+            "Storage: {} in {} chart(s)",
+            format_bytes(storage.total_size),
+            storage.charts
+        ))
+        .id_salt("storage")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("Library: {}", format_bytes(storage.total_size)));
+                ui.separator();
+                ui.label(format!("Charts: {}", storage.charts));
+                ui.separator();
+                match storage.free_space {
+                    Some(free) => ui.label(format!("Free space: {}", format_bytes(free))),
+                    None => ui.label("Free space: unknown"),
+                };
+            });
+
+            let largest = storage.largest(5);
+            if largest.is_empty() {
+                return;
             }
+            ui.label(egui::RichText::new("Largest charts").strong());
+            egui::Grid::new("largest_charts")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (oaci, size) in largest {
+                        if ui.link(oaci).on_hover_text("Open the chart").clicked() {
+                            self.open_pdf(oaci);
+                        }
+                        ui.label(format_bytes(size));
+                        ui.end_row();
+                    }
+                });
         });
     }
 
-    fn download_all(&self) {
-        let status = self.status.clone();
-        let vac_entries = self.vac_entries.clone();
-        let downloader = self.downloader.clone();
-        let sort_column = self.sort_column;
-        let sort_ascending = self.sort_ascending;
+    /// Route box: resolve the aerodromes of a route and select or download their charts
+    fn show_route(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Route")
+            .default_open(!self.route_input.is_empty())
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.route_input)
+                        .hint_text("OACI codes (e.g. LFPN LFRS LFRN) or flight plan text")
+                        .desired_rows(2)
+                        .desired_width(f32::INFINITY),
+                );
 
-        thread::spawn(move || {
-            let entries = vac_entries.lock().unwrap();
-            let total = entries.len();
-            drop(entries);
+                let route = Route::resolve(&self.route_input, &self.core.state().entries);
+                if route.known.is_empty() && route.unknown.is_empty() {
+                    return;
+                }
 
-            *status.lock().unwrap() = OperationStatus::Downloading { current: 0, total };
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(format!("Charts ({}):", route.known.len()));
+                    ui.label(route.known.join(" "));
+                });
+                if !route.unknown.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(format!("Unknown ({}):", route.unknown.len()));
+                        ui.label(
+                            egui::RichText::new(route.unknown.join(" "))
+                                .color(ui.visuals().warn_fg_color),
+                        )
+                        .on_hover_text("No chart is available for these codes");
+                    });
+                }
 
-            let downloader = downloader.lock().unwrap();
-            match downloader.sync(None) {
-                Ok(_) => {
-                    // Refresh the list to update local status
-                    if let Ok(vacs) = downloader.list_vacs(None) {
-                        let mut entries: Vec<VacEntryWithSelection> =
-                            vacs.into_iter().map(VacEntryWithSelection::new).collect();
+                ui.horizontal(|ui| {
+                    let has_charts = !route.known.is_empty();
+                    if ui
+                        .add_enabled(has_charts, egui::Button::new("Select Route Charts"))
+                        .clicked()
+                    {
+                        self.core.set_selection(&route.known);
+                    }
+                    if ui
+                        .add_enabled(
+                            has_charts && !self.core.is_busy(),
+                            egui::Button::image_and_text(
+                                egui::Image::new(&self.icons.download)
+                                    .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                                "Download / Update Route",
+                            ),
+                        )
+                        .on_hover_text("Download the missing charts and update the outdated ones")
+                        .clicked()
+                    {
+                        self.core.set_selection(&route.known);
+                        self.core
+                            .execute(Command::Download(Some(route.known.clone())));
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.route_input.clear();
+                    }
+                });
+            });
+    }
 
-                        // Apply current sorting
-                        Self::sort_entries_static(&mut entries, sort_column, sort_ascending);
+    /// Open the briefing pack dialog with the local charts among `codes`
+    fn open_briefing(&mut self, codes: Vec<String>) {
+        let codes: Vec<String> = {
+            let state = self.core.state();
+            codes
+                .into_iter()
+                .filter(|code| {
+                    state
+                        .entries
+                        .iter()
+                        .any(|e| e.entry.oaci == *code && e.entry.available_locally)
+                })
+                .collect()
+        };
+        self.briefing = Some(BriefingDialog { codes, cover: true });
+    }
 
-                        *vac_entries.lock().unwrap() = entries;
-                    }
-                    *status.lock().unwrap() = OperationStatus::Idle;
+    fn show_briefing(&mut self, ctx: &egui::Context) {
+        let Some(briefing) = &mut self.briefing else {
+            return;
+        };
+        let mut close = false;
+        let mut export = false;
+
+        egui::Window::new("Export Briefing Pack")
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                if briefing.codes.is_empty() {
+                    ui.label("None of these charts is downloaded.");
+                } else {
+                    ui.label("Charts are merged in this order:");
                 }
-                Err(e) => {
-                    *status.lock().unwrap() =
-                        OperationStatus::Error(format!("Download failed: {}", e));
+
+                let mut move_up: Option<usize> = None;
+                let mut remove: Option<usize> = None;
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (idx, code) in briefing.codes.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{}. {}", idx + 1, code));
+                                if ui.add_enabled(idx > 0, egui::Button::new("^")).clicked() {
+                                    move_up = Some(idx);
+                                }
+                                if ui
+                                    .add_enabled(
+                                        idx + 1 < briefing.codes.len(),
+                                        egui::Button::new("v"),
+                                    )
+                                    .clicked()
+                                {
+                                    move_up = Some(idx + 1);
+                                }
+                                if ui.button("Remove").clicked() {
+                                    remove = Some(idx);
+                                }
+                            });
+                        }
+                    });
+                if let Some(idx) = move_up {
+                    briefing.codes.swap(idx - 1, idx);
                 }
-            }
-        });
+                if let Some(idx) = remove {
+                    briefing.codes.remove(idx);
+                }
+
+                ui.separator();
+                ui.checkbox(
+                    &mut briefing.cover,
+                    "Add a cover page listing the aerodromes and chart versions",
+                );
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!briefing.codes.is_empty(), egui::Button::new("Export..."))
+                        .clicked()
+                    {
+                        export = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if export
+            && let Some(output) = rfd::FileDialog::new()
+                .add_filter("PDF", &["pdf"])
+                .set_file_name("briefing.pdf")
+                .save_file()
+        {
+            self.core.execute(Command::ExportBriefing {
+                codes: briefing.codes.clone(),
+                cover: briefing.cover,
+                output,
+            });
+            close = true;
+        }
+        if close {
+            self.briefing = None;
+        }
     }
 
-    fn download_selected(&self) {
-        let vac_entries = self.vac_entries.clone();
-        let status = self.status.clone();
-        let downloader = self.downloader.clone();
-        let sort_column = self.sort_column;
-        let sort_ascending = self.sort_ascending;
-
-        thread::spawn(move || {
-            let entries = vac_entries.lock().unwrap();
-            let selected_codes: Vec<String> = entries
-                .iter()
-                .filter(|e| e.selected)
-                .map(|e| e.entry.oaci.clone())
-                .collect();
-            let total = selected_codes.len();
-            drop(entries);
-
-            if total == 0 {
-                return;
-            }
+    fn show_archive_export(&mut self, ctx: &egui::Context, codes: &[String]) {
+        let mut close = false;
+        let mut export = false;
+
+        egui::Window::new("Export Selected Charts")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} local chart(s) will be written to a ZIP archive, with a {} file listing their codes, cities and versions.",
+                    codes.len(),
+                    archive::MANIFEST_NAME
+                ));
+                ui.horizontal(|ui| {
+                    ui.label("File names:");
+                    ui.text_edit_singleline(&mut self.ui_state.export_name_template);
+                });
+                ui.label("Use {oaci}, {city} and {version} in the file names.");
+
+                let example = {
+                    let state = self.core.state();
+                    codes.first().and_then(|code| {
+                        state.entries.iter().find(|e| e.entry.oaci == *code).map(|e| {
+                            archive::chart_file_name(
+                                &self.ui_state.export_name_template,
+                                &LocalChart {
+                                    oaci: e.entry.oaci.clone(),
+                                    city: e.entry.city.clone(),
+                                    version: e.entry.version.clone(),
+                                    path: Default::default(),
+                                },
+                            )
+                        })
+                    })
+                };
+                if let Some(example) = example {
+                    ui.label(format!("Example: {}", example));
+                }
 
-            *status.lock().unwrap() = OperationStatus::Downloading { current: 0, total };
+                ui.horizontal(|ui| {
+                    if ui.button("Export...").clicked() {
+                        export = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
 
-            let downloader = downloader.lock().unwrap();
-            match downloader.sync(Some(&selected_codes)) {
-                Ok(_) => {
-                    // Refresh the list to update local status
-                    if let Ok(vacs) = downloader.list_vacs(None) {
-                        let mut new_entries: Vec<VacEntryWithSelection> =
-                            vacs.into_iter().map(VacEntryWithSelection::new).collect();
+        if export
+            && let Some(output) = rfd::FileDialog::new()
+                .add_filter("ZIP archive", &["zip"])
+                .set_file_name("charts.zip")
+                .save_file()
+        {
+            self.core.execute(Command::ExportArchive {
+                codes: codes.to_vec(),
+                name_template: self.ui_state.export_name_template.clone(),
+                output,
+            });
+            self.save_ui_state();
+            close = true;
+        }
+        if close {
+            self.archive_export = None;
+        }
+    }
 
-                        // Apply current sorting
-                        Self::sort_entries_static(&mut new_entries, sort_column, sort_ascending);
+    /// Window listing the mirror targets, with a preview of the pending changes
+    fn show_mirrors(&mut self, ctx: &egui::Context) {
+        let mut directories = self.core.config().mirror_directories;
+        let is_busy = self.core.is_busy();
+        let mut changed = false;
+        let mut open = self.show_mirrors;
+
+        egui::Window::new("Mirrors")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Downloaded charts are copied to these folders after every download, \
+                     update and delete. Charts deleted from the library are removed from them.",
+                );
+                ui.separator();
 
-                        *vac_entries.lock().unwrap() = new_entries;
-                    }
-                    *status.lock().unwrap() = OperationStatus::Idle;
+                if directories.is_empty() {
+                    ui.label("No mirror folder.");
+                }
+                let mut remove: Option<usize> = None;
+                for (idx, directory) in directories.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(directory);
+                        if ui.button("Remove").clicked() {
+                            remove = Some(idx);
+                        }
+                    });
                 }
-                Err(e) => {
-                    *status.lock().unwrap() =
-                        OperationStatus::Error(format!("Download failed: {}", e));
+                if let Some(idx) = remove {
+                    directories.remove(idx);
+                    changed = true;
                 }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add(egui::Button::image_and_text(
+                            egui::Image::new(&self.icons.folder)
+                                .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                            "Add Folder...",
+                        ))
+                        .clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_folder()
+                    {
+                        directories.push(path.display().to_string());
+                        changed = true;
+                    }
+                    if ui
+                        .add_enabled(!directories.is_empty(), egui::Button::new("Preview"))
+                        .on_hover_text("List the changes without copying anything")
+                        .clicked()
+                    {
+                        match self.core.mirror_plans() {
+                            Ok(plans) => self.mirror_preview = Some(plans),
+                            Err(e) => self.core.set_status(OperationStatus::Error(format!(
+                                "Failed to compare mirrors: {}",
+                                e
+                            ))),
+                        }
+                    }
+                    if ui
+                        .add_enabled(
+                            !is_busy && !directories.is_empty(),
+                            egui::Button::new("Sync Now"),
+                        )
+                        .clicked()
+                    {
+                        self.core.execute(Command::SyncMirrors);
+                        self.mirror_preview = None;
+                    }
+                });
+
+                if let Some(plans) = &self.mirror_preview {
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            for plan in plans {
+                                ui.label(egui::RichText::new(plan.summary()).strong());
+                                for action in &plan.actions {
+                                    ui.label(action.to_string());
+                                }
+                            }
+                        });
+                }
+            });
+
+        if changed {
+            self.mirror_preview = None;
+            if let Err(e) = self
+                .core
+                .update_config(|config| config.mirror_directories = directories)
+            {
+                self.core.set_status(OperationStatus::Error(format!(
+                    "Failed to save config: {}",
+                    e
+                )));
+            }
+        }
+        self.show_mirrors = open;
+    }
+
+    fn show_airac(&self, ui: &mut egui::Ui) {
+        let today = Utc::now().date_naive();
+        let current = AiracCycle::current();
+        let next = current.next();
+        let days = next.days_until(today);
+
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "Current cycle: {} until {}",
+                current,
+                current.end().format("%d %b %Y")
+            ));
+            ui.separator();
+            ui.label(format!("Next cycle: {}", next));
+
+            if days <= i64::from(self.core.config().airac_warning_days) {
+                let when = match days {
+                    1 => "tomorrow".to_string(),
+                    days => format!("in {} days", days),
+                };
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 165, 0),
+                    format!("⚠ AIRAC {} becomes effective {}", next.ident(), when),
+                )
+                .on_hover_text("Check for updates once the new cycle is effective");
             }
         });
     }
 
-    fn delete_vac(&self, oaci_code: String) {
-        let status = self.status.clone();
-        let vac_entries = self.vac_entries.clone();
-        let downloader = self.downloader.clone();
+    fn show_history(&mut self, ctx: &egui::Context) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let mut open = true;
+        let mut restore: Option<String> = None;
+        let mut compare: Option<(String, String)> = None;
+        let is_busy = self.core.is_busy();
+
+        egui::Window::new(format!("History of {}", history.oaci))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                if history.editions().is_empty() {
+                    ui.label("No previous edition archived yet.");
+                    return;
+                }
 
-        *status.lock().unwrap() = OperationStatus::Deleting(oaci_code.clone());
+                egui::Grid::new("history_grid")
+                    .striped(true)
+                    .spacing([16.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("Version").strong());
+                        ui.label(egui::RichText::new("Downloaded").strong());
+                        ui.label(egui::RichText::new("Replaced").strong());
+                        ui.label("");
+                        ui.end_row();
+
+                        for edition in history.editions() {
+                            ui.label(&edition.version);
+                            ui.label(edition.downloaded.as_deref().unwrap_or("-"));
+                            ui.label(&edition.archived);
+                            ui.horizontal(|ui| {
+                                if ui.button("Open").clicked()
+                                    && let Err(e) = open::that(history.path(edition))
+                                {
+                                    eprintln!("Failed to open {}: {}", edition.file_name, e);
+                                }
+                                if ui
+                                    .add_enabled(!is_busy, egui::Button::new("Compare"))
+                                    .on_hover_text("Show the changes in the current chart")
+                                    .clicked()
+                                {
+                                    compare =
+                                        Some((edition.file_name.clone(), edition.version.clone()));
+                                }
+                                if ui
+                                    .add_enabled(!is_busy, egui::Button::new("Restore"))
+                                    .on_hover_text("Replace the current chart with this edition")
+                                    .clicked()
+                                {
+                                    restore = Some(edition.file_name.clone());
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                ui.label("Dates are in UTC. The current chart is kept in the history on restore.");
+            });
 
-        thread::spawn(move || {
-            let downloader = downloader.lock().unwrap();
-            match downloader.delete(&oaci_code) {
-                Ok(_) => {
-                    // Update the local status in the list
-                    let mut entries = vac_entries.lock().unwrap();
-                    if let Some(entry) = entries.iter_mut().find(|e| e.entry.oaci == oaci_code) {
-                        entry.entry.available_locally = false;
-                    }
-                    *status.lock().unwrap() = OperationStatus::Idle;
+        if let Some((file_name, version)) = compare {
+            self.core.execute(Command::CompareEdition {
+                oaci: history.oaci.clone(),
+                file_name,
+                version,
+            });
+        }
+        if let Some(file_name) = restore {
+            self.core.execute(Command::RestoreEdition {
+                oaci: history.oaci.clone(),
+                file_name,
+            });
+            open = false;
+        }
+        if !open {
+            self.history = None;
+        }
+    }
+
+    fn open_trash(&mut self) {
+        self.trash = Some(TrashDialog {
+            trash: self.core.trash(),
+            confirm_purge: None,
+        });
+    }
+
+    fn show_trash(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.trash else {
+            return;
+        };
+        let trash = &dialog.trash;
+        let mut open = true;
+        let mut restore: Option<Vec<String>> = None;
+        let mut purge: Option<Vec<String>> = None;
+        let is_busy = self.core.is_busy();
+        let trash_days = self.core.config().trash_days;
+
+        egui::Window::new("Trash")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                if trash.items().is_empty() {
+                    ui.label("The trash is empty.");
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("trash_grid")
+                            .striped(true)
+                            .spacing([16.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label(egui::RichText::new("OACI").strong());
+                                ui.label(egui::RichText::new("City").strong());
+                                ui.label(egui::RichText::new("Version").strong());
+                                ui.label(egui::RichText::new("Size").strong());
+                                ui.label(egui::RichText::new("Deleted").strong());
+                                ui.label("");
+                                ui.end_row();
+
+                                for item in trash.items().iter().rev() {
+                                    ui.label(&item.oaci);
+                                    ui.label(&item.city);
+                                    ui.label(&item.version);
+                                    ui.label(format_bytes(item.file_size.max(0) as u64));
+                                    ui.label(item.deleted.format("%Y-%m-%d %H:%M").to_string());
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .add_enabled(!is_busy, egui::Button::new("Restore"))
+                                            .clicked()
+                                        {
+                                            restore = Some(vec![item.id.clone()]);
+                                        }
+                                        if ui
+                                            .add_enabled(
+                                                !is_busy,
+                                                egui::Button::new("Delete Forever"),
+                                            )
+                                            .clicked()
+                                        {
+                                            dialog.confirm_purge = Some(vec![item.id.clone()]);
+                                        }
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+                if let Some(ids) = dialog.confirm_purge.clone() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "Delete {} chart(s) for good? This cannot be undone.",
+                            ids.len()
+                        ));
+                        if ui
+                            .add_enabled(!is_busy, egui::Button::new("Delete"))
+                            .clicked()
+                        {
+                            purge = Some(ids.clone());
+                        }
+                        if ui.button("Cancel").clicked() {
+                            dialog.confirm_purge = None;
+                        }
+                    });
+                } else {
+                    ui.horizontal(|ui| {
+                        let ids: Vec<String> = trash.items().iter().map(|i| i.id.clone()).collect();
+                        if ui
+                            .add_enabled(!is_busy, egui::Button::new("Restore All"))
+                            .clicked()
+                        {
+                            restore = Some(ids.clone());
+                        }
+                        if ui
+                            .add_enabled(!is_busy, egui::Button::new("Empty Trash"))
+                            .clicked()
+                        {
+                            dialog.confirm_purge = Some(ids);
+                        }
+                    });
                 }
-                Err(e) => {
-                    *status.lock().unwrap() =
-                        OperationStatus::Error(format!("Delete failed: {}", e));
+                if trash_days > 0 {
+                    ui.label(format!(
+                        "Charts are deleted for good after {} days in the trash. Dates are in UTC.",
+                        trash_days
+                    ));
+                } else {
+                    ui.label("Charts stay in the trash until it is emptied. Dates are in UTC.");
                 }
+            });
+
+        if let Some(ids) = purge {
+            match self.core.purge_trash(&ids) {
+                Ok(count) => self.core.set_status(OperationStatus::Completed(format!(
+                    "{} chart(s) permanently deleted",
+                    count
+                ))),
+                Err(e) => self.core.set_status(OperationStatus::Error(format!(
+                    "Failed to empty the trash: {}",
+                    e
+                ))),
             }
-        });
+            self.open_trash();
+        }
+        if let Some(ids) = restore {
+            self.core.execute(Command::RestoreTrash(ids));
+            open = false;
+        }
+        if !open {
+            self.trash = None;
+        }
     }
 
-    fn delete_selected(&self) {
-        let vac_entries = self.vac_entries.clone();
-        let status = self.status.clone();
-        let downloader = self.downloader.clone();
+    /// Toast offering to undo the last deletion for a few seconds
+    fn show_undo(&mut self, ctx: &egui::Context) {
+        let trashed = std::mem::take(&mut self.core.state().last_trashed);
+        if !trashed.is_empty() {
+            self.undo = Some((trashed, Instant::now()));
+        }
+        let Some((ids, deleted)) = &self.undo else {
+            return;
+        };
+        if deleted.elapsed() > Duration::from_secs(10) {
+            self.undo = None;
+            return;
+        }
 
-        thread::spawn(move || {
-            let entries = vac_entries.lock().unwrap();
-            let selected_codes: Vec<String> = entries
-                .iter()
-                .filter(|e| e.selected && e.entry.available_locally)
-                .map(|e| e.entry.oaci.clone())
-                .collect();
-            drop(entries);
+        let mut undo = false;
+        let mut dismiss = false;
+        egui::Area::new(egui::Id::new("undo_toast"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -40.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Deleted {} chart(s)", ids.len()));
+                        if ui
+                            .add_enabled(!self.core.is_busy(), egui::Button::new("Undo"))
+                            .clicked()
+                        {
+                            undo = true;
+                        }
+                        if ui.small_button("✖").clicked() {
+                            dismiss = true;
+                        }
+                    });
+                });
+            });
 
-            if selected_codes.is_empty() {
-                return;
+        if undo {
+            self.core.execute(Command::RestoreTrash(ids.clone()));
+        }
+        if undo || dismiss {
+            self.undo = None;
+        }
+    }
+
+    fn show_comparison(&mut self, ctx: &egui::Context) {
+        if let Some(comparison) = self.core.state().comparison.take() {
+            self.comparison = Some(ComparisonView::new(comparison));
+        }
+        let Some(view) = &mut self.comparison else {
+            return;
+        };
+        let mut open = true;
+
+        egui::Window::new(format!("{}: changes since {}", view.oaci, view.version))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_size([700.0, 600.0])
+            .show(ctx, |ui| {
+                if let Err(e) = &view.result {
+                    ui.colored_label(egui::Color32::RED, e);
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut view.show_text, false, "Drawing");
+                    ui.selectable_value(&mut view.show_text, true, "Text");
+                });
+                ui.separator();
+
+                if view.show_text {
+                    view.show_text_changes(ui);
+                } else {
+                    view.show_drawing_changes(ui);
+                }
+            });
+
+        if !open {
+            self.comparison = None;
+        }
+    }
+
+    fn scan_foreign_files(&mut self) {
+        match self.core.foreign_files() {
+            Ok(files) => {
+                let files = files
+                    .into_iter()
+                    .map(|file| {
+                        let code = file.oaci.clone().unwrap_or_default();
+                        (file, false, code)
+                    })
+                    .collect();
+                self.foreign_files = Some(ForeignFilesDialog {
+                    files,
+                    confirm_delete: false,
+                });
             }
+            Err(e) => self.core.set_status(OperationStatus::Error(format!(
+                "Failed to scan download directory: {}",
+                e
+            ))),
+        }
+    }
+
+    fn show_foreign_files(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.foreign_files else {
+            return;
+        };
+        let mut open = true;
+        let mut adopt: Option<Vec<(String, String)>> = None;
+        let mut delete: Option<Vec<String>> = None;
+        let is_busy = self.core.is_busy();
+        let known_codes: HashSet<String> = self
+            .core
+            .state()
+            .entries
+            .iter()
+            .map(|e| e.entry.oaci.clone())
+            .collect();
+
+        egui::Window::new("Unknown Files")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                if dialog.files.is_empty() {
+                    ui.label("Every PDF file of the download directory is known.");
+                    return;
+                }
 
-            let total = selected_codes.len();
-            let downloader = downloader.lock().unwrap();
+                ui.label("These PDF files are in the download directory but not in the library.");
+                ui.label("Adopt a file to use it as the local copy of a chart, or delete it.");
+                ui.separator();
 
-            for (idx, oaci_code) in selected_codes.iter().enumerate() {
-                *status.lock().unwrap() =
-                    OperationStatus::Deleting(format!("{} ({}/{})", oaci_code, idx + 1, total));
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("foreign_files")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.label("");
+                                ui.label(egui::RichText::new("File").strong());
+                                ui.label(egui::RichText::new("Size").strong());
+                                ui.label(egui::RichText::new("OACI Code").strong());
+                                ui.end_row();
+
+                                for (file, selected, code) in &mut dialog.files {
+                                    ui.checkbox(selected, "");
+                                    ui.label(&file.file_name);
+                                    ui.label(format_bytes(file.size));
+                                    ui.horizontal(|ui| {
+                                        ui.add(
+                                            egui::TextEdit::singleline(code).desired_width(60.0),
+                                        );
+                                        if !code.is_empty()
+                                            && !known_codes.contains(&code.to_uppercase())
+                                        {
+                                            ui.colored_label(egui::Color32::RED, "Unknown");
+                                        }
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    });
 
-                match downloader.delete(oaci_code) {
-                    Ok(_) => {
-                        // Update the local status in the list
-                        let mut entries = vac_entries.lock().unwrap();
-                        if let Some(entry) = entries.iter_mut().find(|e| e.entry.oaci == *oaci_code)
+                let selected: Vec<&(ForeignFile, bool, String)> =
+                    dialog.files.iter().filter(|(_, s, _)| *s).collect();
+                let adoptable: Vec<(String, String)> = selected
+                    .iter()
+                    .map(|(file, _, code)| (file.file_name.clone(), code.to_uppercase()))
+                    .filter(|(_, code)| known_codes.contains(code))
+                    .collect();
+
+                ui.separator();
+                if dialog.confirm_delete {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Delete {} file(s)?", selected.len()));
+                        if ui.button("Delete").clicked() {
+                            delete = Some(
+                                selected
+                                    .iter()
+                                    .map(|(f, _, _)| f.file_name.clone())
+                                    .collect(),
+                            );
+                        }
+                        if ui.button("Cancel").clicked() {
+                            dialog.confirm_delete = false;
+                        }
+                    });
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                !is_busy && !adoptable.is_empty(),
+                                egui::Button::new(format!("Adopt Selected ({})", adoptable.len())),
+                            )
+                            .on_hover_text("Selected files with a known OACI code")
+                            .clicked()
                         {
-                            entry.entry.available_locally = false;
-                            entry.selected = false; // Deselect after deletion
+                            adopt = Some(adoptable.clone());
                         }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to delete {}: {}", oaci_code, e);
-                    }
+                        if ui
+                            .add_enabled(
+                                !is_busy && !selected.is_empty(),
+                                egui::Button::new("Delete Selected"),
+                            )
+                            .clicked()
+                        {
+                            dialog.confirm_delete = true;
+                        }
+                    });
                 }
+            });
+
+        if let Some(files) = adopt {
+            self.core.execute(Command::AdoptFiles(files));
+            open = false;
+        }
+        if let Some(file_names) = delete {
+            match self.core.delete_foreign_files(&file_names) {
+                Ok(count) => self.core.set_status(OperationStatus::Completed(format!(
+                    "{} file(s) deleted",
+                    count
+                ))),
+                Err(e) => self.core.set_status(OperationStatus::Error(format!(
+                    "Failed to delete files: {}",
+                    e
+                ))),
             }
+            self.scan_foreign_files();
+            return;
+        }
+        if !open {
+            self.foreign_files = None;
+        }
+    }
 
-            *status.lock().unwrap() = OperationStatus::Idle;
+    /// Background sync, history and disk space settings
+    ///
+    /// Edits are kept in `sync_draft` and saved once finished: when a value
+    /// stops being dragged or typed, or right away for the other controls.
+    fn show_sync_settings(&mut self, ui: &mut egui::Ui) {
+        let saved = self.core.config();
+        let mut config = self.sync_draft.take().unwrap_or_else(|| saved.clone());
+        let mut finished = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Background Sync:");
+            egui::ComboBox::from_id_salt("sync_schedule")
+                .selected_text(config.sync_schedule.label())
+                .show_ui(ui, |ui| {
+                    for schedule in SyncSchedule::ALL {
+                        finished |= ui
+                            .selectable_value(&mut config.sync_schedule, schedule, schedule.label())
+                            .changed();
+                    }
+                });
+
+            if config.sync_schedule == SyncSchedule::Interval {
+                ui.label("every");
+                let response =
+                    ui.add(egui::DragValue::new(&mut config.sync_interval_hours).range(1..=168));
+                finished |= response.drag_stopped() || response.lost_focus();
+                ui.label("hours");
+            }
+
+            if config.sync_schedule != SyncSchedule::Off {
+                finished |= ui
+                    .checkbox(&mut config.auto_update, "Auto-update outdated charts")
+                    .changed();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("History: keep");
+            let response = ui.add(egui::DragValue::new(&mut config.history_versions).range(0..=20));
+            finished |= response.drag_stopped() || response.lost_focus();
+            ui.label("previous editions of each chart (0 to disable)");
         });
+        finished |= ui
+            .checkbox(
+                &mut config.ignore_low_disk_space,
+                "Download even when the charts may not fit on disk",
+            )
+            .changed();
+
+        let modified = config.sync_schedule != saved.sync_schedule
+            || config.sync_interval_hours != saved.sync_interval_hours
+            || config.auto_update != saved.auto_update
+            || config.history_versions != saved.history_versions
+            || config.ignore_low_disk_space != saved.ignore_low_disk_space;
+        if !modified {
+            return;
+        }
+        if !finished {
+            self.sync_draft = Some(config);
+            return;
+        }
+
+        let result = self.core.update_config(|current| {
+            current.sync_schedule = config.sync_schedule;
+            current.sync_interval_hours = config.sync_interval_hours;
+            current.auto_update = config.auto_update;
+            current.history_versions = config.history_versions;
+            current.ignore_low_disk_space = config.ignore_low_disk_space;
+        });
+        if let Err(e) = result {
+            self.core.set_status(OperationStatus::Error(format!(
+                "Failed to save config: {}",
+                e
+            )));
+        }
     }
 
-    fn update_vac(&self, oaci_code: String) {
-        let status = self.status.clone();
-        let vac_entries = self.vac_entries.clone();
-        let downloader = self.downloader.clone();
-        let needs_update_cache = self.needs_update_cache.clone();
-        let sort_column = self.sort_column;
-        let sort_ascending = self.sort_ascending;
+    fn save_config(&mut self) {
+        let config = self.core.config();
+        let download_directory = self.download_dir_input.trim().to_string();
+        if download_directory == config.download_directory {
+            return;
+        }
 
-        *status.lock().unwrap() = OperationStatus::Downloading {
-            current: 1,
-            total: 1,
-        };
+        // Ask what to do with the local charts, if any
+        let has_local = self
+            .core
+            .state()
+            .entries
+            .iter()
+            .any(|e| e.entry.available_locally);
+        if has_local {
+            self.relocation_prompt = Some(download_directory);
+        } else {
+            self.relocate(download_directory, RelocationMode::Move);
+        }
+    }
 
-        thread::spawn(move || {
-            let downloader = downloader.lock().unwrap();
-            // Use sync with specific OACI code to update this entry
-            match downloader.sync(Some(std::slice::from_ref(&oaci_code))) {
-                Ok(_) => {
-                    // Clear the needs_update cache for this entry
-                    needs_update_cache.lock().unwrap().remove(&oaci_code);
+    /// Transfer the local charts to a new download directory and switch to it
+    fn relocate(&mut self, download_directory: String, mode: RelocationMode) {
+        let mut config = self.core.config();
+        config.download_directory = download_directory;
+        self.core.execute(Command::Relocate(config, mode));
+    }
 
-                    // Refresh the list to update the entry
-                    if let Ok(vacs) = downloader.list_vacs(None) {
-                        let mut new_entries: Vec<VacEntryWithSelection> =
-                            vacs.into_iter().map(VacEntryWithSelection::new).collect();
+    /// Switch to a new download directory, forgetting all local charts
+    fn reset_library(&mut self, download_directory: String) {
+        let mut config = self.core.config();
+        config.download_directory = download_directory;
 
-                        // Apply current sorting
-                        Self::sort_entries_static(&mut new_entries, sort_column, sort_ascending);
+        // Save to file
+        match config.save() {
+            Ok(_) => {
+                println!("Configuration saved!");
 
-                        *vac_entries.lock().unwrap() = new_entries;
+                match self.core.reset(&config) {
+                    Ok(_) => {
+                        // Refresh the VAC list to update local availability with new path
+                        self.core.execute(Command::FetchList);
+                    }
+                    Err(e) => {
+                        self.core.set_status(OperationStatus::Error(format!(
+                            "Failed to reinitialize: {}",
+                            e
+                        )));
                     }
-                    *status.lock().unwrap() = OperationStatus::Idle;
-                }
-                Err(e) => {
-                    *status.lock().unwrap() =
-                        OperationStatus::Error(format!("Update failed: {}", e));
                 }
             }
-        });
-    }
-
-    fn check_needs_update(&self, oaci_code: String) {
-        let downloader = self.downloader.clone();
-        let needs_update_cache = self.needs_update_cache.clone();
-
-        thread::spawn(move || {
-            let downloader = downloader.lock().unwrap();
-            match downloader.needs_update(&oaci_code) {
-                Ok(needs_update) => {
-                    let mut cache = needs_update_cache.lock().unwrap();
-                    cache.insert(oaci_code, needs_update);
-                }
-                Err(_) => {
-                    // If we can't determine, assume it doesn't need update
-                    let mut cache = needs_update_cache.lock().unwrap();
-                    cache.insert(oaci_code, false);
-                }
+            Err(e) => {
+                self.core.set_status(OperationStatus::Error(format!(
+                    "Failed to save config: {}",
+                    e
+                )));
             }
-        });
-    }
-
-    fn sort_entries(&mut self) {
-        let mut entries = self.vac_entries.lock().unwrap();
-        Self::sort_entries_static(&mut entries, self.sort_column, self.sort_ascending);
+        }
     }
 
-    fn sort_entries_static(
-        entries: &mut [VacEntryWithSelection],
-        sort_column: SortColumn,
-        sort_ascending: bool,
-    ) {
-        match sort_column {
-            SortColumn::Oaci => {
-                entries.sort_by(|a, b| {
-                    let cmp = a.entry.oaci.cmp(&b.entry.oaci);
-                    if sort_ascending { cmp } else { cmp.reverse() }
-                });
-            }
-            SortColumn::City => {
-                entries.sort_by(|a, b| {
-                    let cmp = a.entry.city.cmp(&b.entry.city);
-                    if sort_ascending { cmp } else { cmp.reverse() }
+    fn show_relocation_prompt(&mut self, ctx: &egui::Context, download_directory: &str) {
+        let current = self.core.config().download_directory;
+        let local_count = self
+            .core
+            .state()
+            .entries
+            .iter()
+            .filter(|e| e.entry.available_locally)
+            .count();
+        let mut close = false;
+
+        egui::Window::new("Change Download Location")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} downloaded chart(s) are stored in {}.",
+                    local_count, current
+                ));
+                ui.label(format!(
+                    "What should happen to them when switching to {}?",
+                    download_directory
+                ));
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Move Charts")
+                        .on_hover_text("Move the charts, then remove them from the old location")
+                        .clicked()
+                    {
+                        self.relocate(download_directory.to_string(), RelocationMode::Move);
+                        close = true;
+                    }
+                    if ui
+                        .button("Copy Charts")
+                        .on_hover_text("Copy the charts and keep the old location untouched")
+                        .clicked()
+                    {
+                        self.relocate(download_directory.to_string(), RelocationMode::Copy);
+                        close = true;
+                    }
+                    if ui
+                        .button("Reset Library")
+                        .on_hover_text(
+                            "Forget all downloaded charts; they will have to be downloaded again",
+                        )
+                        .clicked()
+                    {
+                        self.reset_library(download_directory.to_string());
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.download_dir_input = current.clone();
+                        close = true;
+                    }
                 });
-            }
+            });
+
+        if close {
+            self.relocation_prompt = None;
         }
     }
 
-    fn save_config(&mut self) {
-        // Update config with new download directory
-        self.config.download_directory = self.download_dir_input.clone();
+    fn show_report(&mut self, ctx: &egui::Context, report: &DownloadReport) {
+        let mut close = false;
+
+        egui::Window::new("Download Report")
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label(format!("Downloaded: {}", report.succeeded.len()));
+                ui.label(format!("Already up to date: {}", report.up_to_date));
+                ui.label(format!("Failed: {}", report.failed.len()));
+
+                if !report.retried.is_empty() {
+                    ui.separator();
+                    ui.label(egui::RichText::new("Retried").strong());
+                    let retried: Vec<String> = report
+                        .retried
+                        .iter()
+                        .map(|(oaci, attempts)| format!("{} ({} attempts)", oaci, attempts))
+                        .collect();
+                    ui.label(retried.join(", "));
+                }
 
-        // Save to file
-        match self.config.save() {
-            Ok(_) => {
-                println!("Configuration saved!");
+                if !report.failed.is_empty() {
+                    ui.separator();
+                    ui.label(egui::RichText::new("Failed").strong());
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("failed_downloads")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (oaci, reason) in &report.failed {
+                                        ui.label(oaci);
+                                        ui.label(reason);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
 
-                // Delete the old database file to reset the cache
-                if std::path::Path::new(&self.config.database_path).exists() {
-                    match std::fs::remove_file(&self.config.database_path) {
-                        Ok(_) => println!("Deleted old database cache"),
-                        Err(e) => println!("Warning: Could not delete old database: {}", e),
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !report.failed.is_empty() && !self.core.is_busy(),
+                            egui::Button::image_and_text(
+                                egui::Image::new(&self.icons.refresh)
+                                    .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                                "Retry Failed",
+                            ),
+                        )
+                        .clicked()
+                    {
+                        self.core
+                            .execute(Command::Download(Some(report.failed_codes())));
+                        close = true;
                     }
-                }
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if close {
+            self.core.state().report = None;
+        }
+    }
 
-                // Reinitialize VacDownloader with new paths (creates fresh database)
-                match vac_downloader::VacDownloader::new(
-                    &self.config.database_path,
-                    &self.config.download_directory,
-                ) {
-                    Ok(new_downloader) => {
-                        *self.downloader.lock().unwrap() = new_downloader;
-                        println!("VacDownloader reinitialized with new download location");
-                        println!("Fresh database created");
+    fn show_verify_report(&mut self, ctx: &egui::Context, report: &VerifyReport) {
+        let mut close = false;
+
+        egui::Window::new("Library Verification")
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label(format!("Charts checked: {}", report.checked));
+                ui.label(format!("Problems found: {}", report.problems.len()));
+                if !report.recorded.is_empty() {
+                    ui.label(format!(
+                        "Checksums recorded for {} chart(s) that had none",
+                        report.recorded.len()
+                    ));
+                }
 
-                        // Refresh the VAC list to update local availability with new path
-                        self.fetch_vac_list();
+                if !report.problems.is_empty() {
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("verify_problems")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (oaci, problem) in &report.problems {
+                                        ui.label(oaci);
+                                        ui.label(problem.to_string());
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
 
-                        *self.status.lock().unwrap() = OperationStatus::Idle;
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !report.problems.is_empty() && !self.core.is_busy(),
+                            egui::Button::image_and_text(
+                                egui::Image::new(&self.icons.download)
+                                    .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                                "Re-download All",
+                            ),
+                        )
+                        .clicked()
+                    {
+                        self.core
+                            .execute(Command::Redownload(report.problem_codes()));
                     }
-                    Err(e) => {
-                        *self.status.lock().unwrap() =
-                            OperationStatus::Error(format!("Failed to reinitialize: {}", e));
+                    if ui.button("Close").clicked() {
+                        close = true;
                     }
-                }
-            }
-            Err(e) => {
-                *self.status.lock().unwrap() =
-                    OperationStatus::Error(format!("Failed to save config: {}", e));
-            }
+                });
+            });
+
+        if close {
+            self.core.state().verify_report = None;
         }
     }
 
     fn open_pdf(&self, oaci_code: &str) {
-        let downloader = self.downloader.lock().unwrap();
-        match downloader.get_pdf_path(oaci_code) {
+        match self.core.pdf_path(oaci_code) {
             Ok(path) => {
                 if let Err(e) = open::that(&path) {
                     eprintln!("Failed to open PDF for {}: {}", oaci_code, e);
@@ -490,10 +1814,24 @@ impl VacDownloaderApp {
 }
 
 impl eframe::App for VacDownloaderApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_ui_state();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Request repaint to keep UI responsive during async operations
         ctx.request_repaint();
 
+        // Track the window geometry so it can be saved on exit
+        ctx.input(|i| {
+            if let Some(rect) = i.viewport().inner_rect {
+                self.ui_state.window_size = Some([rect.width(), rect.height()]);
+            }
+            if let Some(rect) = i.viewport().outer_rect {
+                self.ui_state.window_position = Some([rect.min.x, rect.min.y]);
+            }
+        });
+
         // Top panel with toolbar
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -501,9 +1839,7 @@ impl eframe::App for VacDownloaderApp {
 
                 ui.separator();
 
-                let status_guard = self.status.lock().unwrap();
-                let is_busy = status_guard.is_busy();
-                drop(status_guard);
+                let is_busy = self.core.is_busy();
 
                 if ui
                     .add_enabled(
@@ -516,9 +1852,21 @@ impl eframe::App for VacDownloaderApp {
                     )
                     .clicked()
                 {
-                    self.fetch_vac_list();
+                    self.core.execute(Command::FetchList);
                 }
 
+                let (estimate, free_space) = {
+                    let state = self.core.state();
+                    (state.download_estimate(), state.storage.free_space)
+                };
+                let hint = match free_space {
+                    Some(free) => format!(
+                        "About {} to download, {} free",
+                        format_bytes(estimate),
+                        format_bytes(free)
+                    ),
+                    None => format!("About {} to download", format_bytes(estimate)),
+                };
                 if ui
                     .add_enabled(
                         !is_busy,
@@ -528,14 +1876,30 @@ impl eframe::App for VacDownloaderApp {
                             "Download All",
                         ),
                     )
+                    .on_hover_text(hint)
                     .clicked()
                 {
-                    self.download_all();
+                    self.core.execute(Command::Download(None));
+                }
+                if free_space.is_some_and(|free| estimate > free) {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 165, 0),
+                        "⚠ Not enough disk space",
+                    )
+                    .on_hover_text(format!(
+                        "Downloading all charts needs about {}",
+                        format_bytes(estimate)
+                    ));
                 }
 
-                let entries = self.vac_entries.lock().unwrap();
-                let has_selection = entries.iter().any(|e| e.selected);
-                drop(entries);
+                let state = self.core.state();
+                let has_selection = state.entries.iter().any(|e| e.selected);
+                // Check if any selected entries are available locally
+                let has_local_selection = state
+                    .entries
+                    .iter()
+                    .any(|e| e.selected && e.entry.available_locally);
+                drop(state);
 
                 if ui
                     .add_enabled(
@@ -548,16 +1912,10 @@ impl eframe::App for VacDownloaderApp {
                     )
                     .clicked()
                 {
-                    self.download_selected();
+                    let selected_codes = self.core.state().selected_codes();
+                    self.core.execute(Command::Download(Some(selected_codes)));
                 }
 
-                // Check if any selected entries are available locally
-                let entries = self.vac_entries.lock().unwrap();
-                let has_local_selection = entries
-                    .iter()
-                    .any(|e| e.selected && e.entry.available_locally);
-                drop(entries);
-
                 if ui
                     .add_enabled(
                         !is_busy && has_local_selection,
@@ -570,14 +1928,72 @@ impl eframe::App for VacDownloaderApp {
                     .clicked()
                 {
                     // Collect selected OACI codes for confirmation
-                    let entries = self.vac_entries.lock().unwrap();
-                    let selected_codes: Vec<String> = entries
-                        .iter()
-                        .filter(|e| e.selected && e.entry.available_locally)
-                        .map(|e| e.entry.oaci.clone())
-                        .collect();
-                    drop(entries);
-                    self.delete_confirmation = Some(selected_codes);
+                    self.delete_confirmation = Some(self.core.state().selected_local_codes());
+                }
+
+                ui.separator();
+
+                if ui
+                    .add_enabled(
+                        !is_busy,
+                        egui::Button::image_and_text(
+                            egui::Image::new(&self.icons.status_update)
+                                .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                            "Check Updates",
+                        ),
+                    )
+                    .on_hover_text("Check all local charts for newer versions")
+                    .clicked()
+                {
+                    self.core.execute(Command::CheckAllUpdates);
+                }
+
+                let outdated_codes = self.core.state().outdated_codes();
+                if ui
+                    .add_enabled(
+                        !is_busy && !outdated_codes.is_empty(),
+                        egui::Button::image_and_text(
+                            egui::Image::new(&self.icons.download)
+                                .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                            format!("Update All Outdated ({})", outdated_codes.len()),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.core.execute(Command::Download(Some(outdated_codes)));
+                }
+
+                if ui
+                    .add_enabled(!is_busy, egui::Button::new("Verify Library"))
+                    .on_hover_text("Check local charts for missing or damaged files")
+                    .clicked()
+                {
+                    self.core.execute(Command::VerifyLibrary);
+                }
+
+                ui.separator();
+
+                if ui
+                    .add_enabled(
+                        !is_busy && has_local_selection,
+                        egui::Button::new("Export Briefing"),
+                    )
+                    .on_hover_text("Merge the selected local charts into a single PDF")
+                    .clicked()
+                {
+                    let codes = self.core.state().selected_local_codes();
+                    self.open_briefing(codes);
+                }
+
+                if ui
+                    .add_enabled(
+                        !is_busy && has_local_selection,
+                        egui::Button::new("Export Selected"),
+                    )
+                    .on_hover_text("Write the selected local charts to a ZIP archive")
+                    .clicked()
+                {
+                    self.archive_export = Some(self.core.state().selected_local_codes());
                 }
             });
         });
@@ -585,12 +2001,50 @@ impl eframe::App for VacDownloaderApp {
         // Bottom panel with status bar
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
+                let status = self.core.status();
+                if let OperationStatus::Downloading(progress) = &status {
+                    ui.add(
+                        egui::ProgressBar::new(progress.fraction())
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                }
                 ui.label("Status:");
-                let status = self.status.lock().unwrap();
                 ui.label(status.to_string());
+
+                if status.is_cancellable() {
+                    let cancelling = self.core.is_cancelling();
+                    if ui
+                        .add_enabled(
+                            !cancelling,
+                            egui::Button::image_and_text(
+                                egui::Image::new(&self.icons.close)
+                                    .fit_to_exact_size(egui::vec2(16.0, 16.0)),
+                                "Cancel",
+                            ),
+                        )
+                        .on_hover_text("Stop after the current chart")
+                        .clicked()
+                    {
+                        self.core.cancel();
+                    }
+                    if cancelling {
+                        ui.label("Cancelling after the current chart...");
+                    }
+                }
+
+                let last_sync = self.core.state().last_sync;
+                if let Some(last_sync) = last_sync {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(format!("Last sync: {}", last_sync.format("%Y-%m-%d %H:%M")));
+                    });
+                }
             });
         });
 
+        // Side panel with favorites and collections
+        self.show_collections_panel(ctx);
+
         // Central panel with VAC list
         egui::CentralPanel::default().show(ctx, |ui| {
             // Download location configuration section
@@ -598,9 +2052,7 @@ impl eframe::App for VacDownloaderApp {
                 ui.label("Download Location:");
                 ui.text_edit_singleline(&mut self.download_dir_input);
 
-                let status_guard = self.status.lock().unwrap();
-                let is_busy = status_guard.is_busy();
-                drop(status_guard);
+                let is_busy = self.core.is_busy();
 
                 if ui
                     .add_enabled(
@@ -632,16 +2084,34 @@ impl eframe::App for VacDownloaderApp {
                         self.download_dir_input = path.display().to_string();
                     }
                 }
+
+                if ui
+                    .add_enabled(!is_busy, egui::Button::new("Scan Folder"))
+                    .on_hover_text("Find PDF files unknown to the application")
+                    .clicked()
+                {
+                    self.scan_foreign_files();
+                }
+
+                if ui
+                    .button("Mirrors")
+                    .on_hover_text("Folders kept in sync with the downloaded charts")
+                    .clicked()
+                {
+                    self.show_mirrors = true;
+                }
             });
             ui.horizontal(|ui| {
                 ui.add(
                     egui::Image::new(&self.icons.info).fit_to_exact_size(egui::vec2(16.0, 16.0)),
                 );
-                ui.label("Warning: changing location will reset the database");
+                ui.label("Changing location moves or copies the downloaded charts");
             });
+            self.show_sync_settings(ui);
             ui.separator();
 
             ui.heading("Available VAC Charts");
+            self.show_airac(ui);
             ui.separator();
 
             // Search box
@@ -668,16 +2138,82 @@ impl eframe::App for VacDownloaderApp {
                 );
                 ui.label("Filter by OACI code or city name");
             });
+            ui.horizontal(|ui| {
+                ui.label("Show:");
+                for filter in StatusFilter::ALL {
+                    if ui
+                        .selectable_value(&mut self.status_filter, filter, filter.label())
+                        .clicked()
+                        && filter == StatusFilter::Outdated
+                    {
+                        // Hidden rows are never checked lazily, check them all now
+                        let has_unchecked = {
+                            let state = self.core.state();
+                            state.entries.iter().any(|e| {
+                                e.entry.available_locally
+                                    && !state.needs_update.contains_key(&e.entry.oaci)
+                            })
+                        };
+                        if has_unchecked && !self.core.is_busy() {
+                            self.core.execute(Command::CheckAllUpdates);
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Sort by:");
+                let (mut column, mut ascending) = {
+                    let state = self.core.state();
+                    (state.sort_column, state.sort_ascending)
+                };
+                let previous = (column, ascending);
+                egui::ComboBox::from_id_salt("sort_column")
+                    .selected_text(column.label())
+                    .show_ui(ui, |ui| {
+                        for option in SortColumn::ALL {
+                            ui.selectable_value(&mut column, option, option.label());
+                        }
+                    });
+                let direction = if ascending { "Ascending" } else { "Descending" };
+                if ui.button(direction).clicked() {
+                    ascending = !ascending;
+                }
+                if (column, ascending) != previous {
+                    self.set_sort(column, ascending);
+                }
+            });
+            self.show_route(ui);
+            self.show_storage(ui);
             ui.separator();
 
             egui::ScrollArea::vertical()
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
                     ui.set_width(ui.available_width());
-                    let mut entries = self.vac_entries.lock().unwrap();
-                    let status_guard = self.status.lock().unwrap();
-                    let is_busy = status_guard.is_busy();
-                    drop(status_guard);
+                    let view_codes = self.collections.codes(&self.view);
+                    let mut state_guard = self.core.state();
+                    let mut filtered_indices =
+                        state_guard.filtered_indices(&self.search_query, self.status_filter);
+                    if let Some(codes) = view_codes {
+                        filtered_indices
+                            .retain(|&idx| codes.contains(&state_guard.entries[idx].entry.oaci));
+                    }
+                    let history_enabled = self.core.config().history_versions > 0;
+                    let current_cycle = AiracCycle::current();
+                    let previous_cycle: HashSet<String> = state_guard
+                        .entries
+                        .iter()
+                        .filter(|e| state_guard.predates_cycle(e, &current_cycle))
+                        .map(|e| e.entry.oaci.clone())
+                        .collect();
+                    let state = &mut *state_guard;
+                    let is_busy = state.status.is_busy();
+                    let entries = &mut state.entries;
+                    let needs_update_cache = &state.needs_update;
+                    let download_dates = &state.download_dates;
+                    let storage = &state.storage;
+                    let mut sort_column = state.sort_column;
+                    let mut sort_ascending = state.sort_ascending;
 
                     // Collect actions to perform after releasing the lock
                     let mut update_oaci: Option<String> = None;
@@ -685,38 +2221,19 @@ impl eframe::App for VacDownloaderApp {
                     let mut open_pdf_oaci: Option<String> = None;
                     let mut need_sort = false;
                     let mut oaci_codes_to_check: Vec<String> = Vec::new();
+                    let mut toggle_favorite: Option<String> = None;
+                    let mut history_oaci: Option<String> = None;
 
                     if entries.is_empty() {
                         ui.centered_and_justified(|ui| {
                             ui.label("No VAC entries loaded. Click Refresh to fetch the list.");
                         });
                     } else {
-                        // Filter entries based on search query - collect indices
-                        let search_query_lower = self.search_query.to_lowercase();
-                        let filtered_indices: Vec<usize> = entries
-                            .iter()
-                            .enumerate()
-                            .filter(|(_, entry)| {
-                                if search_query_lower.is_empty() {
-                                    true
-                                } else {
-                                    entry
-                                        .entry
-                                        .oaci
-                                        .to_lowercase()
-                                        .contains(&search_query_lower)
-                                        || entry
-                                            .entry
-                                            .city
-                                            .to_lowercase()
-                                            .contains(&search_query_lower)
-                                }
-                            })
-                            .map(|(idx, _)| idx)
-                            .collect();
-
                         // Display count of filtered results
-                        if !search_query_lower.is_empty() {
+                        if !self.search_query.is_empty()
+                            || self.status_filter != StatusFilter::All
+                            || self.view != ChartView::All
+                        {
                             ui.label(format!(
                                 "Showing {} of {} entries",
                                 filtered_indices.len(),
@@ -729,15 +2246,20 @@ impl eframe::App for VacDownloaderApp {
                             .striped(true)
                             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
                             .column(Column::exact(30.0)) // Checkbox
+                            .column(Column::exact(24.0)) // Favorite
                             .column(Column::exact(80.0)) // OACI Code
                             .column(Column::remainder()) // City - fills remaining space
-                            .column(Column::exact(60.0)) // Local status
-                            .column(Column::exact(150.0)) // Actions
+                            .column(Column::exact(70.0)) // Local status
+                            .column(Column::exact(100.0)) // Download date
+                            .column(Column::exact(80.0)) // File size
+                            .column(Column::exact(210.0)) // Actions
                             .header(20.0, |mut header| {
                                 // Checkbox column header
                                 header.col(|ui| {
-                                    let all_filtered_selected =
-                                        filtered_indices.iter().all(|&idx| entries[idx].selected);
+                                    let all_filtered_selected = !filtered_indices.is_empty()
+                                        && filtered_indices
+                                            .iter()
+                                            .all(|&idx| entries[idx].selected);
                                     let mut select_all = all_filtered_selected;
                                     if ui.checkbox(&mut select_all, "").changed() {
                                         // Toggle all filtered entries
@@ -747,48 +2269,31 @@ impl eframe::App for VacDownloaderApp {
                                     }
                                 });
 
+                                // Favorite column header
+                                header.col(|ui| {
+                                    ui.label(egui::RichText::new("★").strong());
+                                });
+
                                 // OACI Code column header
                                 header.col(|ui| {
-                                    let oaci_label = if self.sort_column == SortColumn::Oaci {
-                                        let arrow = if self.sort_ascending { "^" } else { "v" };
-                                        format!("OACI Code {}", arrow)
-                                    } else {
-                                        "OACI Code".to_string()
-                                    };
-                                    if ui
-                                        .button(egui::RichText::new(oaci_label).strong())
-                                        .clicked()
-                                    {
-                                        if self.sort_column == SortColumn::Oaci {
-                                            self.sort_ascending = !self.sort_ascending;
-                                        } else {
-                                            self.sort_column = SortColumn::Oaci;
-                                            self.sort_ascending = true;
-                                        }
-                                        need_sort = true;
-                                    }
+                                    need_sort |= sort_button(
+                                        ui,
+                                        "OACI Code",
+                                        SortColumn::Oaci,
+                                        &mut sort_column,
+                                        &mut sort_ascending,
+                                    );
                                 });
 
                                 // City column header
                                 header.col(|ui| {
-                                    let city_label = if self.sort_column == SortColumn::City {
-                                        let arrow = if self.sort_ascending { "^" } else { "v" };
-                                        format!("City {}", arrow)
-                                    } else {
-                                        "City".to_string()
-                                    };
-                                    if ui
-                                        .button(egui::RichText::new(city_label).strong())
-                                        .clicked()
-                                    {
-                                        if self.sort_column == SortColumn::City {
-                                            self.sort_ascending = !self.sort_ascending;
-                                        } else {
-                                            self.sort_column = SortColumn::City;
-                                            self.sort_ascending = true;
-                                        }
-                                        need_sort = true;
-                                    }
+                                    need_sort |= sort_button(
+                                        ui,
+                                        "City",
+                                        SortColumn::City,
+                                        &mut sort_column,
+                                        &mut sort_ascending,
+                                    );
                                 });
 
                                 // Local status column header
@@ -796,11 +2301,39 @@ impl eframe::App for VacDownloaderApp {
                                     ui.with_layout(
                                         egui::Layout::right_to_left(egui::Align::Center),
                                         |ui| {
-                                            ui.label(egui::RichText::new("Local").strong());
+                                            need_sort |= sort_button(
+                                                ui,
+                                                "Local",
+                                                SortColumn::Status,
+                                                &mut sort_column,
+                                                &mut sort_ascending,
+                                            );
                                         },
                                     );
                                 });
 
+                                // Download date column header
+                                header.col(|ui| {
+                                    need_sort |= sort_button(
+                                        ui,
+                                        "Downloaded",
+                                        SortColumn::DownloadDate,
+                                        &mut sort_column,
+                                        &mut sort_ascending,
+                                    );
+                                });
+
+                                // File size column header
+                                header.col(|ui| {
+                                    need_sort |= sort_button(
+                                        ui,
+                                        "Size",
+                                        SortColumn::FileSize,
+                                        &mut sort_column,
+                                        &mut sort_ascending,
+                                    );
+                                });
+
                                 // Actions column header
                                 header.col(|ui| {
                                     ui.label(egui::RichText::new("Actions").strong());
@@ -817,6 +2350,24 @@ impl eframe::App for VacDownloaderApp {
                                             ui.checkbox(&mut entry.selected, "");
                                         });
 
+                                        // Favorite column
+                                        row.col(|ui| {
+                                            let favorite =
+                                                self.collections.is_favorite(&entry.entry.oaci);
+                                            let (star, hint) = if favorite {
+                                                ("★", "Remove from favorites")
+                                            } else {
+                                                ("☆", "Add to favorites")
+                                            };
+                                            if ui
+                                                .add(egui::Button::new(star).frame(false))
+                                                .on_hover_text(hint)
+                                                .clicked()
+                                            {
+                                                toggle_favorite = Some(entry.entry.oaci.clone());
+                                            }
+                                        });
+
                                         // OACI code column - clickable if available locally
                                         row.col(|ui| {
                                             if entry.entry.available_locally {
@@ -841,11 +2392,8 @@ impl eframe::App for VacDownloaderApp {
 
                                         // Check update status once for this entry (if available locally)
                                         let needs_update = if entry.entry.available_locally {
-                                            let needs_update_cache =
-                                                self.needs_update_cache.lock().unwrap();
                                             let status =
                                                 needs_update_cache.get(&entry.entry.oaci).copied();
-                                            drop(needs_update_cache);
 
                                             // If we don't have the status yet, mark it for checking
                                             if status.is_none() {
@@ -897,6 +2445,34 @@ impl eframe::App for VacDownloaderApp {
                                             );
                                         });
 
+                                        // Download date column
+                                        row.col(|ui| match download_dates.get(&entry.entry.oaci) {
+                                            Some(date) if entry.entry.available_locally => {
+                                                let day = date.get(..10).unwrap_or(date);
+                                                if previous_cycle.contains(&entry.entry.oaci) {
+                                                    ui.colored_label(
+                                                        egui::Color32::from_rgb(255, 165, 0),
+                                                        format!("⚠ {}", day),
+                                                    )
+                                                    .on_hover_text(format!(
+                                                        "{} UTC, before {} became effective",
+                                                        date, current_cycle
+                                                    ));
+                                                } else {
+                                                    ui.label(day)
+                                                        .on_hover_text(format!("{} UTC", date));
+                                                }
+                                            }
+                                            _ => {
+                                                ui.label("-");
+                                            }
+                                        });
+
+                                        // File size column
+                                        row.col(|ui| {
+                                            ui.label(format_bytes(storage.file_size(&entry.entry)));
+                                        });
+
                                         // Actions column
                                         row.col(|ui| {
                                             if entry.entry.available_locally {
@@ -914,10 +2490,25 @@ impl eframe::App for VacDownloaderApp {
                                                     update_oaci = Some(entry.entry.oaci.clone());
                                                 }
 
-                                                if ui.button("Delete").clicked() {
+                                                if ui
+                                                    .add_enabled(
+                                                        !is_busy,
+                                                        egui::Button::new("Delete"),
+                                                    )
+                                                    .clicked()
+                                                {
                                                     delete_oaci =
                                                         Some(vec![entry.entry.oaci.clone()]);
                                                 }
+
+                                                if history_enabled
+                                                    && ui
+                                                        .button("History")
+                                                        .on_hover_text("Previous editions")
+                                                        .clicked()
+                                                {
+                                                    history_oaci = Some(entry.entry.oaci.clone());
+                                                }
                                             }
                                         });
                                     });
@@ -925,20 +2516,25 @@ impl eframe::App for VacDownloaderApp {
                             });
                     }
 
-                    drop(entries);
+                    drop(state_guard);
 
                     // Execute actions after releasing the lock
                     if need_sort {
-                        self.sort_entries();
+                        self.set_sort(sort_column, sort_ascending);
+                    }
+
+                    if let Some(oaci) = toggle_favorite {
+                        self.collections.toggle_favorite(&oaci);
+                        self.save_collections();
                     }
 
                     // Check update status for entries that need it
                     for oaci in oaci_codes_to_check {
-                        self.check_needs_update(oaci);
+                        self.core.execute(Command::CheckNeedsUpdate(oaci));
                     }
 
                     if let Some(oaci) = update_oaci {
-                        self.update_vac(oaci);
+                        self.core.execute(Command::Download(Some(vec![oaci])));
                     }
                     if let Some(oaci) = open_pdf_oaci {
                         self.open_pdf(&oaci);
@@ -946,11 +2542,44 @@ impl eframe::App for VacDownloaderApp {
                     if let Some(oaci_codes) = delete_oaci {
                         self.delete_confirmation = Some(oaci_codes);
                     }
+                    if let Some(oaci) = history_oaci {
+                        self.history = Some(self.core.chart_history(&oaci));
+                    }
                 });
         });
 
+        // Relocation dialog
+        if let Some(download_directory) = self.relocation_prompt.clone() {
+            self.show_relocation_prompt(ctx, &download_directory);
+        }
+
+        // Mirror targets window
+        if self.show_mirrors {
+            self.show_mirrors(ctx);
+        }
+
+        // Briefing pack export dialog
+        self.show_briefing(ctx);
+
+        // Unknown files window
+        self.show_foreign_files(ctx);
+
+        // Chart history window
+        self.show_history(ctx);
+        self.show_comparison(ctx);
+
+        // Trash window and undo toast
+        self.show_trash(ctx);
+        self.show_undo(ctx);
+
+        // Archive export dialog
+        if let Some(codes) = self.archive_export.clone() {
+            self.show_archive_export(ctx, &codes);
+        }
+
         // Delete confirmation dialog
         if let Some(oaci_codes) = &self.delete_confirmation.clone() {
+            let is_busy = self.core.is_busy();
             egui::Window::new("Confirm Delete")
                 .collapsible(false)
                 .resizable(false)
@@ -969,13 +2598,10 @@ impl eframe::App for VacDownloaderApp {
                             ui.label(format!("Entries: {}", oaci_codes.join(", ")));
                         }
                     }
+                    ui.label("Deleted charts are kept in the trash and can be restored.");
                     ui.horizontal(|ui| {
-                        if ui.button("Yes").clicked() {
-                            if oaci_codes.len() == 1 {
-                                self.delete_vac(oaci_codes[0].clone());
-                            } else {
-                                self.delete_selected();
-                            }
+                        if ui.add_enabled(!is_busy, egui::Button::new("Yes")).clicked() {
+                            self.core.execute(Command::Delete(oaci_codes.clone()));
                             self.delete_confirmation = None;
                         }
                         if ui.button("No").clicked() {
@@ -984,5 +2610,78 @@ impl eframe::App for VacDownloaderApp {
                     });
                 });
         }
+
+        // Download stopped for lack of disk space
+        let shortfall = self.core.state().space_shortfall.clone();
+        if let Some(shortfall) = shortfall {
+            let is_busy = self.core.is_busy();
+            egui::Window::new("Not Enough Disk Space")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(format!("{}.", shortfall));
+                    ui.label("Nothing was downloaded. If the disk fills up during the download, it stops and the partial files are removed.");
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(!is_busy, egui::Button::new("Download Anyway"))
+                            .clicked()
+                        {
+                            self.core.state().space_shortfall = None;
+                            self.core.execute(Command::DownloadAnyway(shortfall.clone()));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.core.state().space_shortfall = None;
+                        }
+                    });
+                });
+        }
+
+        // Library verification window
+        let verify_report = self.core.state().verify_report.clone();
+        if let Some(report) = verify_report {
+            self.show_verify_report(ctx, &report);
+        }
+
+        // Download report window
+        let report = self.core.state().report.clone();
+        if let Some(report) = report {
+            // A single chart updated on the first attempt needs no report
+            let trivial = report.failed.is_empty()
+                && report.retried.is_empty()
+                && report.succeeded.len() + report.up_to_date <= 1;
+            if trivial {
+                self.core.state().report = None;
+            } else {
+                self.show_report(ctx, &report);
+            }
+        }
+    }
+}
+
+/// Show a sortable column header, returning true when the sort order changed
+fn sort_button(
+    ui: &mut egui::Ui,
+    label: &str,
+    column: SortColumn,
+    sort_column: &mut SortColumn,
+    sort_ascending: &mut bool,
+) -> bool {
+    let text = if *sort_column == column {
+        let arrow = if *sort_ascending { "^" } else { "v" };
+        format!("{} {}", label, arrow)
+    } else {
+        label.to_string()
+    };
+
+    if !ui.button(egui::RichText::new(text).strong()).clicked() {
+        return false;
+    }
+
+    if *sort_column == column {
+        *sort_ascending = !*sort_ascending;
+    } else {
+        *sort_column = column;
+        *sort_ascending = true;
     }
+    true
 }
@@ -0,0 +1,185 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use crate::models::LocalChart;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+/// Default naming of the chart files in an archive
+pub const DEFAULT_NAME_TEMPLATE: &str = "{oaci} - {city}";
+/// Name of the manifest file written at the root of the archive
+pub const MANIFEST_NAME: &str = "manifest.csv";
+
+/// File name of a chart in an archive
+///
+/// `{oaci}`, `{city}` and `{version}` in the template are replaced by the chart
+/// details. Characters not allowed in file names are replaced by `_` and the
+/// `.pdf` extension is added if missing.
+pub fn chart_file_name(template: &str, chart: &LocalChart) -> String {
+    let template = if template.trim().is_empty() {
+        DEFAULT_NAME_TEMPLATE
+    } else {
+        template
+    };
+
+    let name: String = template
+        .replace("{oaci}", &chart.oaci)
+        .replace("{city}", &chart.city)
+        .replace("{version}", &chart.version)
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let name = name.trim();
+    if name.to_lowercase().ends_with(".pdf") {
+        name.to_string()
+    } else {
+        format!("{}.pdf", name)
+    }
+}
+
+/// Write charts to a ZIP archive with a manifest listing their details
+///
+/// Charts whose name is already used in the archive get a numbered suffix.
+/// Returns the number of charts written.
+pub fn export_zip(charts: &[LocalChart], name_template: &str, output: &Path) -> Result<usize> {
+    let mut zip = ZipWriter::new(File::create(output)?);
+    let options = SimpleFileOptions::default();
+    let mut used_names = HashSet::new();
+    let mut manifest = String::from("oaci,city,version,file,sha256\n");
+
+    for chart in charts {
+        let bytes = fs::read(&chart.path)
+            .map_err(|e| format!("Failed to read {}: {}", chart.path.display(), e))?;
+        let name = unique_name(&mut used_names, chart_file_name(name_template, chart));
+
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(&bytes)?;
+
+        let hash = format!("{:x}", Sha256::digest(&bytes));
+        let fields = [&chart.oaci, &chart.city, &chart.version, &name, &hash];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        manifest.push_str(&line.join(","));
+        manifest.push('\n');
+    }
+
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(manifest.as_bytes())?;
+    zip.finish()?;
+
+    Ok(charts.len())
+}
+
+/// Make a file name unique among those already used, e.g. `LFPN.pdf` -> `LFPN (2).pdf`
+fn unique_name(used_names: &mut HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut counter = 2;
+    while used_names.contains(&candidate.to_lowercase()) || candidate == MANIFEST_NAME {
+        let stem = name.strip_suffix(".pdf").unwrap_or(&name);
+        candidate = format!("{} ({}).pdf", stem, counter);
+        counter += 1;
+    }
+    used_names.insert(candidate.to_lowercase());
+    candidate
+}
+
+/// Quote a CSV field when needed
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn chart(oaci: &str, city: &str) -> LocalChart {
+        LocalChart {
+            oaci: oaci.to_string(),
+            city: city.to_string(),
+            version: "2025-01".to_string(),
+            path: PathBuf::from(format!("{}.pdf", oaci)),
+        }
+    }
+
+    #[test]
+    fn names_charts_after_the_template() {
+        let chart = chart("LFPN", "Toussus/Le Noble");
+        assert_eq!(chart_file_name("", &chart), "LFPN - Toussus_Le Noble.pdf");
+        assert_eq!(
+            chart_file_name("{oaci}_{version}", &chart),
+            "LFPN_2025-01.pdf"
+        );
+        assert_eq!(chart_file_name("{oaci}.PDF", &chart), "LFPN.PDF");
+    }
+
+    #[test]
+    fn numbers_duplicate_names() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_name(&mut used, "LFPN.pdf".to_string()), "LFPN.pdf");
+        assert_eq!(
+            unique_name(&mut used, "lfpn.pdf".to_string()),
+            "lfpn (2).pdf"
+        );
+        assert_eq!(
+            unique_name(&mut used, "LFPN.pdf".to_string()),
+            "LFPN (3).pdf"
+        );
+        assert_eq!(csv_field("Saint-Cyr, l'École"), "\"Saint-Cyr, l'École\"");
+    }
+
+    #[test]
+    fn writes_charts_and_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut charts = vec![chart("LFPN", "Toussus"), chart("LFRS", "Nantes")];
+        for chart in &mut charts {
+            chart.path = dir.path().join(&chart.path);
+            fs::write(&chart.path, &chart.oaci).unwrap();
+        }
+        let output = dir.path().join("charts.zip");
+
+        assert_eq!(export_zip(&charts, "{oaci}", &output).unwrap(), 2);
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"LFPN.pdf") && names.contains(&MANIFEST_NAME));
+        let mut manifest = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name(MANIFEST_NAME).unwrap(), &mut manifest)
+            .unwrap();
+        assert!(
+            manifest.starts_with("oaci,city,version,file,sha256\nLFPN,Toussus,2025-01,LFPN.pdf,")
+        );
+    }
+}
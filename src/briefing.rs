@@ -0,0 +1,208 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use crate::models::LocalChart;
+use lopdf::content::{Content, Operation};
+use lopdf::{Bookmark, Dictionary, Document, Object, ObjectId, Stream, dictionary};
+use std::path::Path;
+
+/// A4 page size, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+/// Number of chart lines on each cover page
+const COVER_LINES_PER_PAGE: usize = 40;
+/// Page attributes a page can inherit from its ancestors in the page tree
+const INHERITABLE_KEYS: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Merge chart PDFs into a single booklet, in the given order
+///
+/// When `cover` is set, the booklet starts with a cover page listing the
+/// aerodromes and chart versions included. Each chart gets a bookmark.
+/// Returns the number of pages written.
+pub fn export_briefing(charts: &[LocalChart], cover: bool, output: &Path) -> Result<usize> {
+    let mut document = Document::with_version("1.5");
+    let pages_id = document.new_object_id();
+    let mut kids: Vec<ObjectId> = Vec::new();
+
+    if cover {
+        for lines in charts.chunks(COVER_LINES_PER_PAGE) {
+            let total = kids.is_empty().then_some(charts.len());
+            let page_id = add_cover_page(&mut document, pages_id, lines, total);
+            kids.push(page_id);
+        }
+    }
+
+    for chart in charts {
+        let mut source = Document::load(&chart.path)
+            .map_err(|e| format!("Failed to read {}: {}", chart.path.display(), e))?;
+        source.renumber_objects_with(document.max_id + 1);
+        document.max_id = source.max_id;
+
+        let source_pages: Vec<ObjectId> = source.get_pages().into_values().collect();
+        for &page_id in &source_pages {
+            let mut page = source.get_dictionary(page_id)?.clone();
+            for key in INHERITABLE_KEYS {
+                if !page.has(key)
+                    && let Some(value) = inherited_attribute(&source, &page, key)
+                {
+                    page.set(key, value);
+                }
+            }
+            page.set("Parent", pages_id);
+            source.objects.insert(page_id, Object::Dictionary(page));
+        }
+
+        for (id, object) in source.objects {
+            match object.type_name().unwrap_or(b"") {
+                b"Catalog" | b"Pages" | b"Outlines" | b"Outline" => {}
+                _ => {
+                    document.objects.insert(id, object);
+                }
+            }
+        }
+
+        if let Some(&first_page) = source_pages.first() {
+            let title = format!("{} - {} ({})", chart.oaci, chart.city, chart.version);
+            document.add_bookmark(Bookmark::new(title, [0.0, 0.0, 0.0], 0, first_page), None);
+        }
+        kids.extend(source_pages);
+    }
+
+    let page_count = kids.len();
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids.into_iter().map(Object::Reference).collect::<Vec<_>>(),
+            "Count" => page_count as i64,
+        }),
+    );
+
+    let mut catalog = dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    };
+    if let Some(outline_id) = document.build_outline() {
+        catalog.set("Outlines", outline_id);
+        catalog.set("PageMode", "UseOutlines");
+    }
+    let catalog_id = document.add_object(catalog);
+    document.trailer.set("Root", catalog_id);
+
+    document.compress();
+    document.save(output)?;
+
+    Ok(page_count)
+}
+
+/// Look up an attribute a page inherits from its ancestors in the page tree
+fn inherited_attribute(document: &Document, page: &Dictionary, key: &[u8]) -> Option<Object> {
+    let mut parent_id = page.get(b"Parent").and_then(Object::as_reference).ok();
+    while let Some(id) = parent_id {
+        let parent = document.get_dictionary(id).ok()?;
+        if let Ok(value) = parent.get(key) {
+            return Some(value.clone());
+        }
+        parent_id = parent.get(b"Parent").and_then(Object::as_reference).ok();
+    }
+    None
+}
+
+/// Add a cover page listing charts
+///
+/// `total` is the number of charts in the booklet, given for the first cover
+/// page only, which also carries the booklet title.
+fn add_cover_page(
+    document: &mut Document,
+    pages_id: ObjectId,
+    charts: &[LocalChart],
+    total: Option<usize>,
+) -> ObjectId {
+    let font_id = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let bold_font_id = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica-Bold",
+        "Encoding" => "WinAnsiEncoding",
+    });
+
+    let mut operations = Vec::new();
+    let mut y = PAGE_HEIGHT - 80.0;
+    let mut text = |font: &str, size: f32, x: f32, y: f32, value: &str| {
+        operations.extend([
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec![font.into(), size.into()]),
+            Operation::new("Td", vec![x.into(), y.into()]),
+            Operation::new("Tj", vec![Object::string_literal(win_ansi(value))]),
+            Operation::new("ET", vec![]),
+        ]);
+    };
+
+    if let Some(total) = total {
+        text("F2", 24.0, 60.0, y, "Flight Briefing Pack");
+        y -= 30.0;
+        text("F1", 11.0, 60.0, y, &format!("{} chart(s)", total));
+        y -= 40.0;
+    }
+    text("F2", 11.0, 60.0, y, "OACI");
+    text("F2", 11.0, 130.0, y, "City");
+    text("F2", 11.0, 420.0, y, "Version");
+    y -= 20.0;
+    for chart in charts {
+        text("F1", 11.0, 60.0, y, &chart.oaci);
+        text("F1", 11.0, 130.0, y, &chart.city);
+        text("F1", 11.0, 420.0, y, &chart.version);
+        y -= 16.0;
+    }
+
+    let content = Content { operations };
+    let content_id = document.add_object(Stream::new(
+        dictionary! {},
+        content.encode().unwrap_or_default(),
+    ));
+
+    document.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "Resources" => dictionary! {
+            "Font" => dictionary! {
+                "F1" => font_id,
+                "F2" => bold_font_id,
+            },
+        },
+        "MediaBox" => vec![0.into(), 0.into(), PAGE_WIDTH.into(), PAGE_HEIGHT.into()],
+    })
+}
+
+/// Encode text for the standard PDF fonts, replacing unsupported characters
+fn win_ansi(value: &str) -> Vec<u8> {
+    value
+        .chars()
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect()
+}
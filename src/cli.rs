@@ -0,0 +1,715 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use std::io::{self, Write};
+use vac_downloader_gui::config::Config;
+use vac_downloader_gui::core::Core;
+use vac_downloader_gui::trash::{Trash, TrashItem};
+
+/// Everything went fine
+pub const EXIT_OK: i32 = 0;
+/// The operation failed, or some charts could not be processed
+pub const EXIT_FAILURE: i32 = 1;
+/// A requested OACI code is unknown or not available locally
+pub const EXIT_NOT_FOUND: i32 = 3;
+/// `status` found at least one local chart with a newer version available
+pub const EXIT_UPDATES_AVAILABLE: i32 = 4;
+
+/// VAC Downloader - download and manage French Visual Approach Charts
+///
+/// Without a subcommand the graphical interface is started.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+pub struct Args {
+    /// Print machine-readable JSON instead of human-readable text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List all remotely available charts and their local status
+    List {
+        /// Only list charts available locally
+        #[arg(long)]
+        local: bool,
+    },
+    /// Download new charts and update outdated ones
+    Sync {
+        /// OACI codes to sync (all charts when omitted)
+        #[arg(value_name = "OACI")]
+        codes: Vec<String>,
+        /// Download even if the charts may not fit in the free disk space
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete local charts, keeping their files in the trash
+    Delete {
+        /// OACI codes to delete
+        #[arg(value_name = "OACI", required = true)]
+        codes: Vec<String>,
+    },
+    /// List, restore or permanently delete the charts in the trash
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    /// Check local charts for available updates
+    Status {
+        /// OACI codes to check (all local charts when omitted)
+        #[arg(value_name = "OACI")]
+        codes: Vec<String>,
+    },
+    /// Check local charts for missing, truncated or corrupted files
+    Verify {
+        /// Download the damaged or missing charts again
+        #[arg(long)]
+        repair: bool,
+        /// Repair even if the charts may not fit in the free disk space
+        #[arg(long, requires = "repair")]
+        force: bool,
+    },
+    /// Copy the library changes to the mirror folders set in the configuration
+    Mirror {
+        /// Only print the changes that would be made
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the path of a local chart PDF
+    Path {
+        /// OACI code of the chart
+        #[arg(value_name = "OACI")]
+        code: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashAction {
+    /// List the charts in the trash
+    List,
+    /// Put deleted charts back in the library
+    Restore {
+        /// Trash item ids, or OACI codes to restore their latest deletion
+        #[arg(value_name = "ITEM", required = true)]
+        items: Vec<String>,
+    },
+    /// Permanently delete charts from the trash
+    Purge {
+        /// Trash item ids, or OACI codes to purge all their deletions (the whole
+        /// trash when omitted)
+        #[arg(value_name = "ITEM")]
+        items: Vec<String>,
+    },
+}
+
+/// Run a command-line subcommand and return the process exit code
+pub fn run(command: Command, json: bool) -> i32 {
+    // vac_downloader reports its progress on stdout; keep stdout for our results
+    let mut out = take_stdout();
+
+    let config = Config::load();
+    let core = match Core::new(&config) {
+        Ok(core) => core,
+        Err(e) => {
+            return report_error(
+                &mut out,
+                json,
+                &format!("Failed to initialize VacDownloader: {}", e),
+            );
+        }
+    };
+
+    let result = match command {
+        Command::List { local } => list(&core, &mut out, json, local),
+        Command::Sync { codes, force } => sync(&core, &mut out, json, &codes, force),
+        Command::Delete { codes } => delete(&core, &mut out, json, &codes),
+        Command::Trash { action } => trash(&core, &mut out, json, action),
+        Command::Status { codes } => status(&core, &mut out, json, &codes),
+        Command::Verify { repair, force } => verify(&core, &mut out, json, repair, force),
+        Command::Mirror { dry_run } => mirror(&core, &mut out, json, dry_run),
+        Command::Path { code } => path(&core, &mut out, json, &code),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => report_error(&mut out, json, &e.to_string()),
+    }
+}
+
+/// Error of a download, saying how to start it when the disk looks too small
+fn download_error(core: &Core, error: Box<dyn std::error::Error>) -> String {
+    if core.state().space_shortfall.is_some() {
+        format!("{} (use --force to download anyway)", error)
+    } else {
+        error.to_string()
+    }
+}
+
+fn list(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    local_only: bool,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    core.fetch_list()?;
+    let state = core.state();
+    let vacs: Vec<_> = state
+        .entries
+        .iter()
+        .map(|e| &e.entry)
+        .filter(|v| v.available_locally || !local_only)
+        .collect();
+
+    if json {
+        let items: Vec<_> = vacs
+            .iter()
+            .map(|v| {
+                json!({
+                    "oaci": v.oaci,
+                    "city": v.city,
+                    "version": v.version,
+                    "file_name": v.file_name,
+                    "file_size": v.file_size,
+                    "available_locally": v.available_locally,
+                })
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::Value::Array(items))?;
+    } else {
+        for v in vacs {
+            let local = if v.available_locally { "local" } else { "-" };
+            writeln!(out, "{:<6} {:<5} {}", v.oaci, local, v.city)?;
+        }
+    }
+
+    Ok(EXIT_OK)
+}
+
+fn sync(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    codes: &[String],
+    force: bool,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let filter = if codes.is_empty() { None } else { Some(codes) };
+    let stats = core
+        .download(filter, force)
+        .map_err(|e| download_error(core, e))?;
+    let report = core.state().report.clone().unwrap_or_default();
+
+    if json {
+        let failures: Vec<_> = report
+            .failed
+            .iter()
+            .map(|(oaci, reason)| json!({ "oaci": oaci, "reason": reason }))
+            .collect();
+        let retried: Vec<_> = report
+            .retried
+            .iter()
+            .map(|(oaci, attempts)| json!({ "oaci": oaci, "attempts": attempts }))
+            .collect();
+        let value = json!({
+            "total_entries": stats.total_entries,
+            "to_download": stats.to_download,
+            "downloaded": stats.downloaded,
+            "failed": stats.failed,
+            "up_to_date": stats.up_to_date,
+            "verified": stats.verified,
+            "redownloaded_corrupted": stats.redownloaded_corrupted,
+            "retried": retried,
+            "failures": failures,
+        });
+        writeln!(out, "{}", value)?;
+    } else {
+        writeln!(out, "Total entries: {}", stats.total_entries)?;
+        writeln!(out, "Up to date:    {}", stats.up_to_date)?;
+        writeln!(out, "Downloaded:    {}", stats.downloaded)?;
+        writeln!(out, "Retried:       {}", report.retried.len())?;
+        writeln!(out, "Failed:        {}", stats.failed)?;
+        for (oaci, reason) in &report.failed {
+            writeln!(out, "  {:<6} {}", oaci, reason)?;
+        }
+    }
+
+    if stats.failed > 0 {
+        Ok(EXIT_FAILURE)
+    } else if !codes.is_empty() && stats.total_entries == 0 {
+        Ok(EXIT_NOT_FOUND)
+    } else {
+        Ok(EXIT_OK)
+    }
+}
+
+fn delete(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    codes: &[String],
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let codes: Vec<String> = codes.iter().map(|c| c.to_uppercase()).collect();
+    let mut exit_code = EXIT_OK;
+    let mut items = Vec::new();
+    let mut trashed = Vec::new();
+
+    for (code, result) in core.delete(&codes) {
+        match result {
+            Ok(deletion) => {
+                if !deletion.found {
+                    exit_code = exit_code.max(EXIT_NOT_FOUND);
+                }
+                if json {
+                    items.push(json!({
+                        "oaci": deletion.oaci,
+                        "deleted": deletion.found,
+                        "trashed": deletion.trash_id.is_some(),
+                        "trash_id": deletion.trash_id,
+                    }));
+                } else if let Some(id) = &deletion.trash_id {
+                    writeln!(out, "{:<6} moved to trash ({})", deletion.oaci, id)?;
+                } else if deletion.found {
+                    writeln!(out, "{:<6} deleted, no local file", deletion.oaci)?;
+                } else {
+                    writeln!(out, "{:<6} not found", deletion.oaci)?;
+                }
+                if deletion.trash_id.is_some() {
+                    trashed.push(deletion.oaci);
+                }
+            }
+            Err(e) => {
+                exit_code = EXIT_FAILURE;
+                if json {
+                    items.push(json!({ "oaci": code, "error": e.to_string() }));
+                } else {
+                    writeln!(out, "{:<6} failed: {}", code, e)?;
+                }
+            }
+        }
+    }
+
+    if json {
+        writeln!(out, "{}", serde_json::Value::Array(items))?;
+    } else if !trashed.is_empty() {
+        writeln!(
+            out,
+            "Restore with: {} trash restore {}",
+            env!("CARGO_BIN_NAME"),
+            trashed.join(" ")
+        )?;
+    }
+
+    Ok(exit_code)
+}
+
+fn trash(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    action: TrashAction,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let trash = core.trash();
+    match action {
+        TrashAction::List => {
+            if json {
+                let items: Vec<_> = trash
+                    .items()
+                    .iter()
+                    .map(|item| {
+                        json!({
+                            "trash_id": item.id,
+                            "oaci": item.oaci,
+                            "city": item.city,
+                            "version": item.version,
+                            "file_size": item.file_size,
+                            "deleted": item.deleted.to_rfc3339(),
+                        })
+                    })
+                    .collect();
+                writeln!(out, "{}", serde_json::Value::Array(items))?;
+            } else if trash.items().is_empty() {
+                writeln!(out, "The trash is empty")?;
+            } else {
+                for item in trash.items() {
+                    writeln!(
+                        out,
+                        "{:<6} {} UTC  {}",
+                        item.oaci,
+                        item.deleted.format("%Y-%m-%d %H:%M"),
+                        item.id
+                    )?;
+                }
+            }
+            Ok(EXIT_OK)
+        }
+        TrashAction::Restore { items } => {
+            let (ids, unknown) = trash_ids(&trash, &items, true);
+            let mut exit_code = if unknown.is_empty() {
+                EXIT_OK
+            } else {
+                EXIT_NOT_FOUND
+            };
+            let oaci = |id: &str| {
+                trash
+                    .items()
+                    .iter()
+                    .find(|item| item.id == id)
+                    .map_or_else(String::new, |item| item.oaci.clone())
+            };
+
+            let restored = if ids.is_empty() {
+                Vec::new()
+            } else {
+                core.restore_from_trash(&ids)
+            };
+            let mut results = Vec::new();
+            for (id, result) in restored {
+                let oaci = oaci(&id);
+                match result {
+                    Ok(()) => {
+                        if json {
+                            results.push(json!({ "oaci": oaci, "trash_id": id, "restored": true }));
+                        } else {
+                            writeln!(out, "{:<6} restored", oaci)?;
+                        }
+                    }
+                    Err(e) => {
+                        exit_code = EXIT_FAILURE;
+                        if json {
+                            results.push(
+                                json!({ "oaci": oaci, "trash_id": id, "error": e.to_string() }),
+                            );
+                        } else {
+                            writeln!(out, "{:<6} failed: {}", oaci, e)?;
+                        }
+                    }
+                }
+            }
+            report_unknown_items(out, json, &unknown, &mut results)?;
+            if json {
+                writeln!(out, "{}", serde_json::Value::Array(results))?;
+            }
+            Ok(exit_code)
+        }
+        TrashAction::Purge { items } => {
+            let (ids, unknown) = if items.is_empty() {
+                let all = trash.items().iter().map(|item| item.id.clone()).collect();
+                (all, Vec::new())
+            } else {
+                trash_ids(&trash, &items, false)
+            };
+            let purged = core.purge_trash(&ids)?;
+
+            let mut results = Vec::new();
+            report_unknown_items(out, json, &unknown, &mut results)?;
+            if json {
+                writeln!(out, "{}", json!({ "purged": purged, "not_found": results }))?;
+            } else {
+                writeln!(out, "{} chart(s) permanently deleted", purged)?;
+            }
+            Ok(if unknown.is_empty() {
+                EXIT_OK
+            } else {
+                EXIT_NOT_FOUND
+            })
+        }
+    }
+}
+
+/// Trash item ids matching the given ids or OACI codes, and the arguments matching none
+///
+/// An OACI code stands for its latest deletion when `latest_only` is set, for all
+/// of them otherwise.
+fn trash_ids(trash: &Trash, args: &[String], latest_only: bool) -> (Vec<String>, Vec<String>) {
+    let mut ids: Vec<String> = Vec::new();
+    let mut unknown = Vec::new();
+    for arg in args {
+        let mut matches: Vec<&TrashItem> = trash
+            .items()
+            .iter()
+            .filter(|item| item.id == *arg)
+            .collect();
+        if matches.is_empty() {
+            matches = trash
+                .items()
+                .iter()
+                .filter(|item| item.oaci.eq_ignore_ascii_case(arg))
+                .collect();
+            if latest_only {
+                matches = matches.split_off(matches.len().saturating_sub(1));
+            }
+        }
+        if matches.is_empty() {
+            unknown.push(arg.clone());
+        }
+        for item in matches {
+            if !ids.contains(&item.id) {
+                ids.push(item.id.clone());
+            }
+        }
+    }
+    (ids, unknown)
+}
+
+/// Report the trash arguments that matched no item
+fn report_unknown_items(
+    out: &mut dyn Write,
+    json: bool,
+    unknown: &[String],
+    results: &mut Vec<serde_json::Value>,
+) -> io::Result<()> {
+    for arg in unknown {
+        if json {
+            results.push(json!({ "item": arg, "error": "not in the trash" }));
+        } else {
+            writeln!(out, "{:<6} not in the trash", arg)?;
+        }
+    }
+    Ok(())
+}
+
+fn status(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    codes: &[String],
+) -> Result<i32, Box<dyn std::error::Error>> {
+    core.fetch_list()?;
+    let codes: Vec<String> = codes.iter().map(|c| c.to_uppercase()).collect();
+    let vacs: Vec<_> = core
+        .state()
+        .entries
+        .iter()
+        .map(|e| e.entry.clone())
+        .filter(|v| codes.is_empty() || codes.contains(&v.oaci))
+        .collect();
+
+    let mut exit_code = if !codes.is_empty() && vacs.len() < codes.len() {
+        EXIT_NOT_FOUND
+    } else {
+        EXIT_OK
+    };
+    let mut items = Vec::new();
+
+    for v in vacs.iter().filter(|v| v.available_locally) {
+        let state = match core.check_needs_update(&v.oaci) {
+            Ok(true) => {
+                if exit_code == EXIT_OK {
+                    exit_code = EXIT_UPDATES_AVAILABLE;
+                }
+                "outdated"
+            }
+            Ok(false) => "up-to-date",
+            Err(_) => {
+                exit_code = EXIT_FAILURE;
+                "unknown"
+            }
+        };
+
+        if json {
+            items.push(json!({
+                "oaci": v.oaci,
+                "city": v.city,
+                "version": v.version,
+                "status": state,
+            }));
+        } else {
+            writeln!(out, "{:<6} {:<10} {}", v.oaci, state, v.city)?;
+        }
+    }
+
+    for v in vacs.iter().filter(|v| !v.available_locally) {
+        if json {
+            items.push(json!({
+                "oaci": v.oaci,
+                "city": v.city,
+                "version": v.version,
+                "status": "not-downloaded",
+            }));
+        } else if !codes.is_empty() {
+            writeln!(out, "{:<6} {:<10} {}", v.oaci, "missing", v.city)?;
+        }
+    }
+
+    if json {
+        writeln!(out, "{}", serde_json::Value::Array(items))?;
+    }
+
+    Ok(exit_code)
+}
+
+fn verify(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    repair: bool,
+    force: bool,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let report = core.verify_library()?;
+    let codes = report.problem_codes();
+
+    let mut repaired = Vec::new();
+    if repair && !codes.is_empty() {
+        core.redownload(&codes, force)
+            .map_err(|e| download_error(core, e))?;
+        if let Some(download) = &core.state().report {
+            repaired = download.succeeded.clone();
+        }
+    }
+
+    if json {
+        let problems: Vec<_> = report
+            .problems
+            .iter()
+            .map(|(oaci, problem)| {
+                json!({
+                    "oaci": oaci,
+                    "problem": problem.to_string(),
+                    "repaired": repaired.contains(oaci),
+                })
+            })
+            .collect();
+        writeln!(
+            out,
+            "{}",
+            json!({ "checked": report.checked, "problems": problems })
+        )?;
+    } else {
+        for (oaci, problem) in &report.problems {
+            let suffix = if repaired.contains(oaci) {
+                " (repaired)"
+            } else {
+                ""
+            };
+            writeln!(out, "{:<6} {}{}", oaci, problem, suffix)?;
+        }
+        writeln!(
+            out,
+            "{} chart(s) checked, {} problem(s) found",
+            report.checked,
+            report.problems.len()
+        )?;
+    }
+
+    if codes.iter().all(|code| repaired.contains(code)) {
+        Ok(EXIT_OK)
+    } else {
+        Ok(EXIT_FAILURE)
+    }
+}
+
+fn mirror(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    dry_run: bool,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let plans = core.mirror_plans()?;
+    if plans.is_empty() {
+        return Err("No mirror folder set in the configuration (mirror_directories)".into());
+    }
+
+    if json {
+        let items: Vec<_> = plans
+            .iter()
+            .map(|plan| {
+                let actions: Vec<_> = plan.actions.iter().map(|a| a.to_string()).collect();
+                json!({ "target": plan.target, "found": plan.found, "changes": actions })
+            })
+            .collect();
+        writeln!(out, "{}", json!({ "dry_run": dry_run, "targets": items }))?;
+    } else {
+        for plan in &plans {
+            writeln!(out, "{}", plan.summary())?;
+            for action in &plan.actions {
+                writeln!(out, "  {}", action)?;
+            }
+        }
+    }
+
+    if dry_run {
+        return Ok(EXIT_OK);
+    }
+    core.sync_mirrors()?;
+    Ok(EXIT_OK)
+}
+
+fn path(
+    core: &Core,
+    out: &mut dyn Write,
+    json: bool,
+    code: &str,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let code = code.to_uppercase();
+    match core.pdf_path(&code) {
+        Ok(path) => {
+            if json {
+                writeln!(out, "{}", json!({ "oaci": code, "path": path }))?;
+            } else {
+                writeln!(out, "{}", path.display())?;
+            }
+            Ok(EXIT_OK)
+        }
+        Err(e) => {
+            report_error(out, json, &e.to_string());
+            Ok(EXIT_NOT_FOUND)
+        }
+    }
+}
+
+/// Print an error in the requested format and return the generic failure code
+fn report_error(out: &mut dyn Write, json: bool, message: &str) -> i32 {
+    if json {
+        let _ = writeln!(out, "{}", json!({ "error": message }));
+    } else {
+        eprintln!("Error: {}", message);
+    }
+    EXIT_FAILURE
+}
+
+/// Redirect the process stdout to stderr and return a writer on the original stdout
+#[cfg(unix)]
+fn take_stdout() -> Box<dyn Write> {
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    let _ = io::stdout().flush();
+    let stdout_fd = io::stdout().as_raw_fd();
+    // SAFETY: only duplicates standard file descriptors owned by this process
+    unsafe {
+        let saved = libc::dup(stdout_fd);
+        if saved >= 0 {
+            if libc::dup2(io::stderr().as_raw_fd(), stdout_fd) >= 0 {
+                return Box::new(std::fs::File::from_raw_fd(saved));
+            }
+            libc::close(saved);
+        }
+    }
+    Box::new(io::stdout())
+}
+
+#[cfg(not(unix))]
+fn take_stdout() -> Box<dyn Write> {
+    Box::new(io::stdout())
+}
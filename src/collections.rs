@@ -0,0 +1,183 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// A user-named group of charts, e.g. the aerodromes of a trip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    /// OACI codes of the charts in the collection
+    pub codes: Vec<String>,
+}
+
+/// Group of charts shown in the chart table
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum ChartView {
+    #[default]
+    All,
+    Favorites,
+    /// The collection with the given name
+    Collection(String),
+}
+
+/// Favorite charts and named collections, saved next to the configuration file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Collections {
+    /// OACI codes of the favorite charts
+    pub favorites: Vec<String>,
+    pub collections: Vec<Collection>,
+}
+
+impl Collections {
+    /// Get the path to the collections file
+    pub fn file_path() -> PathBuf {
+        Config::config_file_path().with_file_name("collections.toml")
+    }
+
+    /// Load the collections from file, or start with none if it can't be read
+    pub fn load() -> Self {
+        let path = Self::file_path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        match toml::from_str(&contents) {
+            Ok(collections) => collections,
+            Err(e) => {
+                eprintln!("⚠️  Failed to parse collections file: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Save the collections to file
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let toml_string = toml::to_string_pretty(self)?;
+        fs::write(Self::file_path(), toml_string)?;
+        Ok(())
+    }
+
+    /// OACI codes of the charts in a view, or `None` for all charts
+    pub fn codes(&self, view: &ChartView) -> Option<&[String]> {
+        match view {
+            ChartView::All => None,
+            ChartView::Favorites => Some(&self.favorites),
+            ChartView::Collection(name) => Some(self.get(name).map_or(&[], |c| &c.codes)),
+        }
+    }
+
+    fn codes_mut(&mut self, view: &ChartView) -> Option<&mut Vec<String>> {
+        match view {
+            ChartView::All => None,
+            ChartView::Favorites => Some(&mut self.favorites),
+            ChartView::Collection(name) => self.get_mut(name).map(|c| &mut c.codes),
+        }
+    }
+
+    /// Add charts to the favorites or a collection, ignoring those already in it
+    pub fn add_charts(&mut self, view: &ChartView, codes: &[String]) {
+        if let Some(list) = self.codes_mut(view) {
+            for code in codes {
+                if !list.contains(code) {
+                    list.push(code.clone());
+                }
+            }
+            list.sort();
+        }
+    }
+
+    /// Remove charts from the favorites or a collection
+    pub fn remove_charts(&mut self, view: &ChartView, codes: &[String]) {
+        if let Some(list) = self.codes_mut(view) {
+            list.retain(|code| !codes.contains(code));
+        }
+    }
+
+    pub fn is_favorite(&self, oaci: &str) -> bool {
+        self.favorites.iter().any(|code| code == oaci)
+    }
+
+    /// Add a chart to the favorites, or remove it if it already is one
+    pub fn toggle_favorite(&mut self, oaci: &str) {
+        if self.is_favorite(oaci) {
+            self.favorites.retain(|code| code != oaci);
+        } else {
+            self.favorites.push(oaci.to_string());
+            self.favorites.sort();
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Collection> {
+        self.collections.iter().find(|c| c.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Collection> {
+        self.collections.iter_mut().find(|c| c.name == name)
+    }
+
+    /// Create an empty collection; fails if the name is empty or already used
+    pub fn create(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Collection name cannot be empty".to_string());
+        }
+        if self.get(name).is_some() {
+            return Err(format!("A collection named \"{}\" already exists", name));
+        }
+
+        self.collections.push(Collection {
+            name: name.to_string(),
+            codes: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Rename a collection; fails if the new name is empty or already used
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name == name {
+            return Ok(());
+        }
+        if new_name.is_empty() {
+            return Err("Collection name cannot be empty".to_string());
+        }
+        if self.get(new_name).is_some() {
+            return Err(format!(
+                "A collection named \"{}\" already exists",
+                new_name
+            ));
+        }
+
+        if let Some(collection) = self.get_mut(name) {
+            collection.name = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.collections.retain(|c| c.name != name);
+    }
+}
@@ -0,0 +1,407 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use crate::render::{self, RenderedPage, Renderer};
+use image::RgbaImage;
+use lopdf::Document;
+use std::path::Path;
+
+/// Largest number of line pairs compared; longer texts are reported as fully changed
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Smallest difference of a color channel, out of 255, seen as a change of the drawing
+const PIXEL_THRESHOLD: u8 = 48;
+
+/// Side in pixels of the cells grouped into changed areas
+const AREA_CELL: usize = 16;
+
+/// Changed cells this many cells apart or less belong to the same area
+const AREA_GAP: usize = 2;
+
+/// One line of the text comparison of two chart editions
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    /// Line found in both editions
+    Same(String),
+    /// Line only found in the older edition
+    Removed(String),
+    /// Line only found in the newer edition
+    Added(String),
+}
+
+/// Pixel comparison of one page of two chart editions
+#[derive(Debug, Clone)]
+pub struct PageDiff {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA pixels of the newer page faded out, with the drawing only found in the
+    /// older edition in red, the drawing only found in the newer one in green, and
+    /// each changed area framed
+    pub pixels: Vec<u8>,
+    /// Number of pixels that differ between the two editions
+    pub changed_pixels: usize,
+    /// Changed areas of the page, in pixels: x, y, width and height
+    pub areas: Vec<[u32; 4]>,
+    /// Content of either edition that could not be drawn, so changes to it are not shown
+    pub skipped: Vec<String>,
+}
+
+/// Differences between the drawing and the text of two chart editions
+#[derive(Debug, Clone)]
+pub struct ChartComparison {
+    pub old_pages: usize,
+    pub new_pages: usize,
+    /// Page comparisons, or why the pages could not be drawn
+    pub pages: std::result::Result<Vec<PageDiff>, String>,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Outcome of comparing a previous edition of a chart with the current one
+#[derive(Debug, Clone)]
+pub struct EditionComparison {
+    pub oaci: String,
+    /// Version of the previous edition
+    pub version: String,
+    pub result: std::result::Result<ChartComparison, String>,
+}
+
+impl ChartComparison {
+    /// Number of lines removed and added
+    pub fn changes(&self) -> (usize, usize) {
+        self.lines
+            .iter()
+            .fold((0, 0), |(removed, added), line| match line {
+                DiffLine::Same(_) => (removed, added),
+                DiffLine::Removed(_) => (removed + 1, added),
+                DiffLine::Added(_) => (removed, added + 1),
+            })
+    }
+}
+
+/// Compare the drawing and the text of an older and a newer edition of a chart
+pub fn compare_editions(old: &Path, new: &Path) -> Result<ChartComparison> {
+    let (old_pages, old_lines) = extract_lines(old)?;
+    let (new_pages, new_lines) = extract_lines(new)?;
+
+    Ok(ChartComparison {
+        old_pages,
+        new_pages,
+        pages: compare_pages(old, new).map_err(|e| e.to_string()),
+        lines: diff_lines(&old_lines, &new_lines),
+    })
+}
+
+/// Render both editions and compare them page by page
+///
+/// A page only found in one edition, or that could not be drawn, is compared
+/// with a blank page.
+pub fn compare_pages(old: &Path, new: &Path) -> Result<Vec<PageDiff>> {
+    let renderer = Renderer::new()?;
+    let old_pages = renderer.render_file(old, render::DEFAULT_SCALE)?;
+    let new_pages = renderer.render_file(new, render::DEFAULT_SCALE)?;
+    let count = old_pages.len().max(new_pages.len());
+
+    Ok((0..count)
+        .map(|page| {
+            let (old, new) = (old_pages.get(page), new_pages.get(page));
+            let mut diff = diff_page(
+                old.and_then(|p| p.image.as_ref()),
+                new.and_then(|p| p.image.as_ref()),
+            );
+            let notes = |edition: &str, page: Option<&RenderedPage>| {
+                page.into_iter()
+                    .flat_map(|p| &p.skipped)
+                    .map(|skipped| format!("{} edition: {}", edition, skipped))
+                    .collect::<Vec<_>>()
+            };
+            diff.skipped = notes("Previous", old);
+            diff.skipped.extend(notes("Current", new));
+            diff
+        })
+        .collect())
+}
+
+/// Compare the pixels of two renderings of a page
+fn diff_page(old: Option<&RgbaImage>, new: Option<&RgbaImage>) -> PageDiff {
+    let size = |page: Option<&RgbaImage>| page.map_or((0, 0), |p| (p.width(), p.height()));
+    let ((old_width, old_height), (new_width, new_height)) = (size(old), size(new));
+    let (width, height) = (
+        old_width.max(new_width).max(1),
+        old_height.max(new_height).max(1),
+    );
+
+    // Pages are drawn on a white background, so their pixels are opaque
+    let color = |page: Option<&RgbaImage>, x: u32, y: u32| -> [u8; 3] {
+        match page {
+            Some(page) if x < page.width() && y < page.height() => {
+                let [r, g, b, _] = page.get_pixel(x, y).0;
+                [r, g, b]
+            }
+            _ => [255; 3],
+        }
+    };
+    let luma = |[r, g, b]: [u8; 3]| (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let mut changed = vec![false; (width * height) as usize];
+    let mut changed_pixels = 0;
+    for y in 0..height {
+        for x in 0..width {
+            let (before, after) = (color(old, x, y), color(new, x, y));
+            let difference = (0..3)
+                .map(|c| before[c].abs_diff(after[c]))
+                .max()
+                .unwrap_or(0);
+            let i = (y * width + x) as usize;
+            let rgb = if difference >= PIXEL_THRESHOLD {
+                changed[i] = true;
+                changed_pixels += 1;
+                if luma(after) < luma(before) {
+                    [40, 170, 40]
+                } else {
+                    [220, 40, 40]
+                }
+            } else {
+                // Unchanged drawing, faded so that the changes stand out
+                let level = 255 - (255 - luma(after)) * 3 / 10;
+                [level as u8; 3]
+            };
+            pixels[i * 4..i * 4 + 4].copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+        }
+    }
+
+    let areas = changed_areas(&changed, width as usize, height as usize);
+    for area in &areas {
+        frame(&mut pixels, width, height, *area);
+    }
+
+    PageDiff {
+        width,
+        height,
+        pixels,
+        changed_pixels,
+        areas,
+        skipped: Vec::new(),
+    }
+}
+
+/// Group the changed pixels into rectangles, merging changes close to each other
+fn changed_areas(changed: &[bool], width: usize, height: usize) -> Vec<[u32; 4]> {
+    let (columns, rows) = (width.div_ceil(AREA_CELL), height.div_ceil(AREA_CELL));
+    let mut cells = vec![false; columns * rows];
+    for (i, _) in changed.iter().enumerate().filter(|(_, changed)| **changed) {
+        let (x, y) = (i % width, i / width);
+        cells[(y / AREA_CELL) * columns + x / AREA_CELL] = true;
+    }
+
+    let mut areas = Vec::new();
+    let mut seen = vec![false; cells.len()];
+    for start in 0..cells.len() {
+        if !cells[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut pending = vec![start];
+        let (mut left, mut top, mut right, mut bottom) = (columns, rows, 0, 0);
+        while let Some(cell) = pending.pop() {
+            let (column, row) = (cell % columns, cell / columns);
+            (left, top) = (left.min(column), top.min(row));
+            (right, bottom) = (right.max(column), bottom.max(row));
+            for r in row.saturating_sub(AREA_GAP)..(row + AREA_GAP + 1).min(rows) {
+                for c in column.saturating_sub(AREA_GAP)..(column + AREA_GAP + 1).min(columns) {
+                    let next = r * columns + c;
+                    if cells[next] && !seen[next] {
+                        seen[next] = true;
+                        pending.push(next);
+                    }
+                }
+            }
+        }
+        let (x, y) = (left * AREA_CELL, top * AREA_CELL);
+        let w = ((right + 1) * AREA_CELL).min(width) - x;
+        let h = ((bottom + 1) * AREA_CELL).min(height) - y;
+        areas.push([x as u32, y as u32, w as u32, h as u32]);
+    }
+    areas
+}
+
+/// Draw the outline of a changed area
+fn frame(pixels: &mut [u8], width: u32, height: u32, [x, y, w, h]: [u32; 4]) {
+    const COLOR: [u8; 4] = [255, 140, 0, 255];
+    let mut set = |px: u32, py: u32| {
+        if px < width && py < height {
+            let i = ((py * width + px) * 4) as usize;
+            pixels[i..i + 4].copy_from_slice(&COLOR);
+        }
+    };
+    let (x0, y0) = (x.saturating_sub(2), y.saturating_sub(2));
+    let (x1, y1) = (x + w + 1, y + h + 1);
+    for px in x0..=x1 {
+        for offset in 0..2 {
+            set(px, y0 + offset);
+            set(px, y1 - offset);
+        }
+    }
+    for py in y0..=y1 {
+        for offset in 0..2 {
+            set(x0 + offset, py);
+            set(x1 - offset, py);
+        }
+    }
+}
+
+/// Extract the non-empty text lines of a PDF, returning the page count too
+fn extract_lines(path: &Path) -> Result<(usize, Vec<String>)> {
+    let document =
+        Document::load(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let pages: Vec<u32> = document.get_pages().into_keys().collect();
+    let text = document
+        .extract_text(&pages)
+        .map_err(|e| format!("Failed to extract text from {}: {}", path.display(), e))?;
+
+    let lines = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect();
+    Ok((pages.len(), lines))
+}
+
+/// Line diff based on the longest common subsequence of both texts
+fn diff_lines(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    let (n, m) = (old.len(), new.len());
+    if n * m > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .cloned()
+            .map(DiffLine::Removed)
+            .chain(new.iter().cloned().map(DiffLine::Added))
+            .collect();
+    }
+
+    // lcs[i][j]: length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().cloned().map(DiffLine::Removed));
+    lines.extend(new[j..].iter().cloned().map(DiffLine::Added));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('|').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn diff_keeps_common_lines() {
+        let diff = diff_lines(&lines("A|B|C|D"), &lines("A|C|E|D"));
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("A".to_string()),
+                DiffLine::Removed("B".to_string()),
+                DiffLine::Same("C".to_string()),
+                DiffLine::Added("E".to_string()),
+                DiffLine::Same("D".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_counts_removed_and_added_lines() {
+        let comparison = ChartComparison {
+            old_pages: 1,
+            new_pages: 1,
+            pages: Ok(Vec::new()),
+            lines: diff_lines(&lines("A|B"), &lines("C")),
+        };
+        assert_eq!(comparison.changes(), (2, 1));
+    }
+
+    fn page(width: u32, height: u32, dark: &[(u32, u32)]) -> RgbaImage {
+        let mut page = RgbaImage::from_pixel(width, height, image::Rgba([255; 4]));
+        for &(x, y) in dark {
+            page.put_pixel(x, y, image::Rgba([0, 0, 0, 255]));
+        }
+        page
+    }
+
+    #[test]
+    fn identical_pages_have_no_change() {
+        let old = page(64, 64, &[(3, 3), (40, 40)]);
+        let diff = diff_page(Some(&old), Some(&old.clone()));
+        assert_eq!(diff.changed_pixels, 0);
+        assert!(diff.areas.is_empty());
+    }
+
+    #[test]
+    fn changed_pixels_are_coloured_and_grouped() {
+        let old = page(128, 64, &[(2, 2)]);
+        let new = page(128, 64, &[(20, 2), (120, 60)]);
+        let diff = diff_page(Some(&old), Some(&new));
+
+        assert_eq!(diff.changed_pixels, 3);
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * diff.width + x) * 4) as usize;
+            diff.pixels[i..i + 3].to_vec()
+        };
+        assert_eq!(pixel(2, 2), [220, 40, 40]);
+        assert_eq!(pixel(20, 2), [40, 170, 40]);
+        // The two changes close to each other form one area, the far one another
+        assert_eq!(diff.areas, vec![[0, 0, 32, 16], [112, 48, 16, 16]]);
+    }
+
+    #[test]
+    fn a_missing_page_is_compared_with_a_blank_one() {
+        let new = page(32, 32, &[(5, 5)]);
+        let diff = diff_page(None, Some(&new));
+        assert_eq!((diff.width, diff.height), (32, 32));
+        assert_eq!(diff.changed_pixels, 1);
+    }
+}
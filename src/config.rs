@@ -21,7 +21,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +30,92 @@ pub struct Config {
     pub database_path: String,
     /// Directory where VAC PDFs will be downloaded
     pub download_directory: String,
+    /// Number of charts downloaded concurrently
+    #[serde(default = "default_download_workers")]
+    pub download_workers: usize,
+    /// Maximum number of requests per second sent to the SIA server (0 for no limit)
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: f64,
+    /// Number of times a chart is downloaded again after a transient error
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Folders kept in sync with the download directory (e.g. a tablet or cloud-sync folder)
+    #[serde(default)]
+    pub mirror_directories: Vec<String>,
+    /// When to sync in the background while the application is running
+    #[serde(default)]
+    pub sync_schedule: SyncSchedule,
+    /// Hours between background syncs with the "interval" schedule
+    #[serde(default = "default_sync_interval_hours")]
+    pub sync_interval_hours: u32,
+    /// Download the newer versions found by background syncs
+    #[serde(default)]
+    pub auto_update: bool,
+    /// Warn when the next AIRAC cycle becomes effective within this many days
+    #[serde(default = "default_airac_warning_days")]
+    pub airac_warning_days: u32,
+    /// Number of previous editions kept for each chart (0 to keep none)
+    #[serde(default)]
+    pub history_versions: usize,
+    /// Days deleted charts stay in the trash (0 to keep them until emptied)
+    #[serde(default = "default_trash_days")]
+    pub trash_days: u32,
+    /// Start downloads even when the charts may not fit in the free disk space
+    #[serde(default)]
+    pub ignore_low_disk_space: bool,
+}
+
+/// Background synchronization schedule
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncSchedule {
+    /// No background sync
+    #[default]
+    Off,
+    /// Every `sync_interval_hours` hours
+    Interval,
+    /// Once per AIRAC cycle, when a new cycle becomes effective
+    Airac,
+}
+
+impl SyncSchedule {
+    pub const ALL: [SyncSchedule; 3] = [
+        SyncSchedule::Off,
+        SyncSchedule::Interval,
+        SyncSchedule::Airac,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SyncSchedule::Off => "Off",
+            SyncSchedule::Interval => "Every few hours",
+            SyncSchedule::Airac => "Each AIRAC cycle",
+        }
+    }
+}
+
+fn default_download_workers() -> usize {
+    4
+}
+
+fn default_requests_per_second() -> f64 {
+    5.0
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_sync_interval_hours() -> u32 {
+    24
+}
+
+fn default_airac_warning_days() -> u32 {
+    7
+}
+
+fn default_trash_days() -> u32 {
+    30
 }
 
 impl Default for Config {
@@ -44,11 +130,33 @@ impl Default for Config {
                     .join("downloads")
                     .to_string_lossy()
                     .to_string(),
+                download_workers: default_download_workers(),
+                requests_per_second: default_requests_per_second(),
+                max_retries: default_max_retries(),
+                mirror_directories: Vec::new(),
+                sync_schedule: SyncSchedule::Off,
+                sync_interval_hours: default_sync_interval_hours(),
+                auto_update: false,
+                airac_warning_days: default_airac_warning_days(),
+                history_versions: 0,
+                trash_days: default_trash_days(),
+                ignore_low_disk_space: false,
             }
         } else {
             Self {
                 database_path: "vac_cache.db".to_string(),
                 download_directory: "downloads".to_string(),
+                download_workers: default_download_workers(),
+                requests_per_second: default_requests_per_second(),
+                max_retries: default_max_retries(),
+                mirror_directories: Vec::new(),
+                sync_schedule: SyncSchedule::Off,
+                sync_interval_hours: default_sync_interval_hours(),
+                auto_update: false,
+                airac_warning_days: default_airac_warning_days(),
+                history_versions: 0,
+                trash_days: default_trash_days(),
+                ignore_low_disk_space: false,
             }
         }
     }
@@ -100,6 +208,19 @@ impl Config {
         config
     }
 
+    /// Get the directory holding the previous editions of the charts
+    ///
+    /// It lives next to the cache database so that it is not affected by a
+    /// change of download location.
+    pub fn history_directory(&self) -> PathBuf {
+        Path::new(&self.database_path).with_file_name("history")
+    }
+
+    /// Get the directory holding the deleted charts, next to the cache database
+    pub fn trash_directory(&self) -> PathBuf {
+        Path::new(&self.database_path).with_file_name("trash")
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let config_path = Self::config_file_path();
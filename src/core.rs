@@ -0,0 +1,1950 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::airac::AiracCycle;
+use crate::archive;
+use crate::briefing;
+use crate::compare::{self, ChartComparison, EditionComparison};
+use crate::config::{Config, SyncSchedule};
+use crate::database::CacheDatabase;
+use crate::engine::{self, DownloadEngine, EngineEvent};
+use crate::history::ChartHistory;
+use crate::library::{self, ForeignFile, RelocationMode, RelocationReport, VerifyReport};
+use crate::mirror::{MirrorAction, MirrorPlan};
+use crate::models::{
+    Deletion, DownloadProgress, DownloadReport, LocalChart, OperationStatus, SpaceShortfall,
+    VacEntryWithSelection,
+};
+use crate::storage::{self, StorageUsage};
+use crate::trash::{Trash, TrashItem};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use vac_downloader::downloader::SyncStats;
+use vac_downloader::{VacDatabase, VacDownloader, VacEntry};
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// How often the scheduler checks whether a background sync is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+/// Delay before the scheduler tries again after a failed background sync
+const SCHEDULER_RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+
+/// Column used to sort the chart list
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SortColumn {
+    Oaci,
+    City,
+    /// Not downloaded, then outdated, then up to date
+    Status,
+    /// Outdated, then not checked yet, then up to date, then not downloaded
+    Update,
+    DownloadDate,
+    FileSize,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 6] = [
+        SortColumn::Oaci,
+        SortColumn::City,
+        SortColumn::Status,
+        SortColumn::Update,
+        SortColumn::DownloadDate,
+        SortColumn::FileSize,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortColumn::Oaci => "OACI",
+            SortColumn::City => "City",
+            SortColumn::Status => "Local status",
+            SortColumn::Update => "Update available",
+            SortColumn::DownloadDate => "Download date",
+            SortColumn::FileSize => "File size",
+        }
+    }
+}
+
+/// Filter applied to the chart list based on the local status of each chart
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    Downloaded,
+    NotDownloaded,
+    Outdated,
+    PreviousCycle,
+    Selected,
+}
+
+impl StatusFilter {
+    pub const ALL: [StatusFilter; 6] = [
+        StatusFilter::All,
+        StatusFilter::Downloaded,
+        StatusFilter::NotDownloaded,
+        StatusFilter::Outdated,
+        StatusFilter::PreviousCycle,
+        StatusFilter::Selected,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatusFilter::All => "All",
+            StatusFilter::Downloaded => "Downloaded",
+            StatusFilter::NotDownloaded => "Not downloaded",
+            StatusFilter::Outdated => "Outdated",
+            StatusFilter::PreviousCycle => "Previous AIRAC",
+            StatusFilter::Selected => "Selected",
+        }
+    }
+}
+
+/// Chart list and operation state shared between the core and its front-ends
+pub struct State {
+    /// List of VAC entries
+    pub entries: Vec<VacEntryWithSelection>,
+    /// Current operation status
+    pub status: OperationStatus,
+    /// Cache of needs_update status for each OACI code
+    pub needs_update: HashMap<String, bool>,
+    /// OACI codes whose update status is being checked
+    pending_checks: HashSet<String>,
+    /// OACI codes to select once the chart list is loaded
+    pending_selection: HashSet<String>,
+    /// Date each local chart was last downloaded, by OACI code
+    pub download_dates: HashMap<String, String>,
+    /// Current sort column
+    pub sort_column: SortColumn,
+    /// Sort ascending or descending
+    pub sort_ascending: bool,
+    /// Outcome of the last download operation
+    pub report: Option<DownloadReport>,
+    /// Time of the last successful background sync
+    pub last_sync: Option<DateTime<Local>>,
+    /// Outcome of the last library verification
+    pub verify_report: Option<VerifyReport>,
+    /// Disk usage of the local charts, measured when the list is reloaded
+    pub storage: StorageUsage,
+    /// Trash items of the charts removed by the last delete, for undo
+    pub last_trashed: Vec<String>,
+    /// Outcome of the last edition comparison, until a front-end takes it
+    pub comparison: Option<EditionComparison>,
+    /// Last download stopped for lack of disk space, until a front-end takes it
+    pub space_shortfall: Option<SpaceShortfall>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            status: OperationStatus::Idle,
+            needs_update: HashMap::new(),
+            pending_checks: HashSet::new(),
+            pending_selection: HashSet::new(),
+            download_dates: HashMap::new(),
+            sort_column: SortColumn::Oaci,
+            sort_ascending: true,
+            report: None,
+            last_sync: None,
+            verify_report: None,
+            storage: StorageUsage::default(),
+            last_trashed: Vec::new(),
+            comparison: None,
+            space_shortfall: None,
+        }
+    }
+
+    /// OACI codes of the selected entries
+    pub fn selected_codes(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| e.selected)
+            .map(|e| e.entry.oaci.clone())
+            .collect()
+    }
+
+    /// OACI codes of the selection to remember for the next session
+    ///
+    /// Until the chart list is loaded, this is the selection still to apply.
+    pub fn selection_to_save(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            let mut codes: Vec<String> = self.pending_selection.iter().cloned().collect();
+            codes.sort();
+            codes
+        } else {
+            self.selected_codes()
+        }
+    }
+
+    /// Whether a local entry is known to have a newer version available
+    pub fn is_outdated(&self, entry: &VacEntryWithSelection) -> bool {
+        entry.entry.available_locally
+            && self
+                .needs_update
+                .get(&entry.entry.oaci)
+                .copied()
+                .unwrap_or(false)
+    }
+
+    /// Whether the local copy of an entry was downloaded before `cycle` became effective
+    pub fn predates_cycle(&self, entry: &VacEntryWithSelection, cycle: &AiracCycle) -> bool {
+        entry.entry.available_locally
+            && self
+                .download_dates
+                .get(&entry.entry.oaci)
+                .and_then(|date| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok())
+                .is_some_and(|date| date < cycle.effective)
+    }
+
+    /// Indices of the entries matching both the search query (on OACI code or
+    /// city name) and the status filter
+    pub fn filtered_indices(&self, search_query: &str, filter: StatusFilter) -> Vec<usize> {
+        let search_query_lower = search_query.to_lowercase();
+        let cycle = AiracCycle::current();
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                search_query_lower.is_empty()
+                    || entry
+                        .entry
+                        .oaci
+                        .to_lowercase()
+                        .contains(&search_query_lower)
+                    || entry
+                        .entry
+                        .city
+                        .to_lowercase()
+                        .contains(&search_query_lower)
+            })
+            .filter(|(_, entry)| match filter {
+                StatusFilter::All => true,
+                StatusFilter::Downloaded => entry.entry.available_locally,
+                StatusFilter::NotDownloaded => !entry.entry.available_locally,
+                StatusFilter::Outdated => self.is_outdated(entry),
+                StatusFilter::PreviousCycle => self.predates_cycle(entry, &cycle),
+                StatusFilter::Selected => entry.selected,
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// OACI codes of the local entries known to have a newer version available
+    pub fn outdated_codes(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| self.is_outdated(e))
+            .map(|e| e.entry.oaci.clone())
+            .collect()
+    }
+
+    /// Estimated disk space needed to download every missing chart and update
+    /// the outdated ones, from the published file sizes
+    pub fn download_estimate(&self) -> u64 {
+        self.entries
+            .iter()
+            .map(|e| {
+                let size = e.entry.file_size.max(0) as u64;
+                if !e.entry.available_locally {
+                    size
+                } else if self.is_outdated(e) {
+                    size.saturating_sub(self.storage.sizes.get(&e.entry.oaci).copied().unwrap_or(0))
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
+    /// OACI codes of the selected entries that are available locally
+    pub fn selected_local_codes(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| e.selected && e.entry.available_locally)
+            .map(|e| e.entry.oaci.clone())
+            .collect()
+    }
+
+    /// Sort entries according to the current sort column and direction
+    ///
+    /// Entries comparing equal on the sort column are ordered by OACI code.
+    pub fn sort(&mut self) {
+        let mut entries = std::mem::take(&mut self.entries);
+        entries.sort_by(|a, b| {
+            let cmp = self.compare(a, b);
+            let cmp = if self.sort_ascending {
+                cmp
+            } else {
+                cmp.reverse()
+            };
+            cmp.then_with(|| a.entry.oaci.cmp(&b.entry.oaci))
+        });
+        self.entries = entries;
+    }
+
+    /// Compare two entries on the current sort column only
+    fn compare(&self, a: &VacEntryWithSelection, b: &VacEntryWithSelection) -> CmpOrdering {
+        match self.sort_column {
+            SortColumn::Oaci => a.entry.oaci.cmp(&b.entry.oaci),
+            SortColumn::City => a.entry.city.cmp(&b.entry.city),
+            SortColumn::Status => self.status_rank(a).cmp(&self.status_rank(b)),
+            SortColumn::Update => self.update_rank(a).cmp(&self.update_rank(b)),
+            SortColumn::DownloadDate => self
+                .download_dates
+                .get(&a.entry.oaci)
+                .cmp(&self.download_dates.get(&b.entry.oaci)),
+            SortColumn::FileSize => self
+                .storage
+                .file_size(&a.entry)
+                .cmp(&self.storage.file_size(&b.entry)),
+        }
+    }
+
+    fn status_rank(&self, entry: &VacEntryWithSelection) -> u8 {
+        if !entry.entry.available_locally {
+            0
+        } else if self.is_outdated(entry) {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn update_rank(&self, entry: &VacEntryWithSelection) -> u8 {
+        if !entry.entry.available_locally {
+            return 3;
+        }
+        match self.needs_update.get(&entry.entry.oaci) {
+            Some(true) => 0,
+            None => 1,
+            Some(false) => 2,
+        }
+    }
+}
+
+/// Operations that run in the background via [`Core::execute`]
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Fetch the list of available charts
+    FetchList,
+    /// Download (or update) the given charts, or all of them when `None`
+    Download(Option<Vec<String>>),
+    /// Delete the given local charts
+    Delete(Vec<String>),
+    /// Check whether a local chart has a newer version available
+    CheckNeedsUpdate(String),
+    /// Check all local charts for newer versions
+    CheckAllUpdates,
+    /// Transfer the local charts to the download directory of a new configuration
+    Relocate(Config, RelocationMode),
+    /// Merge the given local charts, in order, into a single PDF
+    ExportBriefing {
+        codes: Vec<String>,
+        cover: bool,
+        output: PathBuf,
+    },
+    /// Copy the library changes to the mirror targets
+    SyncMirrors,
+    /// Write the given local charts to a ZIP archive, naming them after a template
+    ExportArchive {
+        codes: Vec<String>,
+        name_template: String,
+        output: PathBuf,
+    },
+    /// Put back a previous edition of a chart, archiving the current one
+    RestoreEdition { oaci: String, file_name: String },
+    /// Compare a previous edition of a chart, named after its version, with the current one
+    CompareEdition {
+        oaci: String,
+        file_name: String,
+        version: String,
+    },
+    /// Check the local charts for missing or damaged files
+    VerifyLibrary,
+    /// Download the given charts again, even if they look up to date
+    Redownload(Vec<String>),
+    /// Add foreign files of the download directory to the cache, as
+    /// (file name, OACI code) pairs
+    AdoptFiles(Vec<(String, String)>),
+    /// Put the given trash items back in the library
+    RestoreTrash(Vec<String>),
+    /// Start a download stopped for lack of disk space, without the space check
+    DownloadAnyway(SpaceShortfall),
+}
+
+/// Notifications sent to subscribers as background work progresses
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The operation status changed
+    StatusChanged(OperationStatus),
+    /// The chart list was reloaded
+    ListUpdated,
+    /// A chart was deleted
+    Deleted(String),
+    /// A download did not start because the charts may not fit on disk
+    LowDiskSpace(SpaceShortfall),
+    /// The update status of a chart is known
+    NeedsUpdateChecked { oaci: String, needs_update: bool },
+}
+
+/// Chart management service, cheap to clone and shared between threads
+#[derive(Clone)]
+pub struct Core {
+    state: Arc<Mutex<State>>,
+    /// Shared VacDownloader instance (benefits from caching)
+    downloader: Arc<Mutex<VacDownloader>>,
+    /// Application configuration
+    config: Arc<Mutex<Config>>,
+    subscribers: Arc<Mutex<Vec<Sender<Event>>>>,
+    /// Set to stop the running operation after the current chart
+    cancel_requested: Arc<AtomicBool>,
+}
+
+impl Core {
+    /// Create the core service using the paths from the configuration
+    pub fn new(config: &Config) -> Result<Self> {
+        let downloader = VacDownloader::new(&config.database_path, &config.download_directory)?;
+
+        Ok(Self {
+            state: Arc::new(Mutex::new(State::new())),
+            downloader: Arc::new(Mutex::new(downloader)),
+            config: Arc::new(Mutex::new(config.clone())),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            cancel_requested: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Lock and access the shared state
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Configuration currently in use
+    pub fn config(&self) -> Config {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// Current operation status
+    pub fn status(&self) -> OperationStatus {
+        self.state().status.clone()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.state().status.is_busy()
+    }
+
+    /// Receive events emitted from now on
+    pub fn subscribe(&self) -> Receiver<Event> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn emit(&self, event: Event) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// Set the operation status and notify subscribers
+    pub fn set_status(&self, status: OperationStatus) {
+        self.state().status = status.clone();
+        self.emit(Event::StatusChanged(status));
+    }
+
+    /// Ask the running download or delete operation to stop after the current chart
+    pub fn cancel(&self) {
+        if self.status().is_cancellable() {
+            self.cancel_requested.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Whether a cancel request is pending
+    pub fn is_cancelling(&self) -> bool {
+        self.cancel_requested.load(Ordering::SeqCst)
+    }
+
+    /// Consume a pending cancel request, returning true if there was one
+    fn take_cancel_request(&self) -> bool {
+        self.cancel_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// Change the sort order and re-sort the list
+    pub fn set_sort(&self, column: SortColumn, ascending: bool) {
+        let mut state = self.state();
+        state.sort_column = column;
+        state.sort_ascending = ascending;
+        state.sort();
+    }
+
+    /// Select exactly the given charts, now or once the chart list is loaded
+    pub fn set_selection(&self, codes: &[String]) {
+        let mut state = self.state();
+        if state.entries.is_empty() {
+            state.pending_selection = codes.iter().cloned().collect();
+        } else {
+            for entry in state.entries.iter_mut() {
+                entry.selected = codes.contains(&entry.entry.oaci);
+            }
+        }
+    }
+
+    /// Run a command on a background thread
+    pub fn execute(&self, command: Command) {
+        // Set the status right away so that the front-end reflects the busy state
+        match &command {
+            Command::FetchList => self.set_status(OperationStatus::FetchingList),
+            Command::Download(codes) => {
+                self.cancel_requested.store(false, Ordering::SeqCst);
+                let total = match codes {
+                    Some(codes) => codes.len(),
+                    None => self.state().entries.len(),
+                };
+                self.set_status(OperationStatus::Downloading(DownloadProgress::new(total)));
+            }
+            Command::Redownload(codes) => {
+                self.cancel_requested.store(false, Ordering::SeqCst);
+                self.set_status(OperationStatus::Downloading(DownloadProgress::new(
+                    codes.len(),
+                )));
+            }
+            Command::DownloadAnyway(shortfall) => {
+                self.cancel_requested.store(false, Ordering::SeqCst);
+                let total = match &shortfall.codes {
+                    Some(codes) => codes.len(),
+                    None => self.state().entries.len(),
+                };
+                self.set_status(OperationStatus::Downloading(DownloadProgress::new(total)));
+            }
+            Command::AdoptFiles(files) => {
+                if let Some((file_name, _)) = files.first() {
+                    self.set_status(OperationStatus::Adopting(file_name.clone()));
+                }
+            }
+            Command::RestoreTrash(_) => {
+                self.set_status(OperationStatus::Restoring("from the trash".to_string()));
+            }
+            Command::VerifyLibrary => {
+                self.cancel_requested.store(false, Ordering::SeqCst);
+                self.set_status(OperationStatus::Verifying {
+                    current: 0,
+                    total: 0,
+                });
+            }
+            Command::Delete(codes) => {
+                self.cancel_requested.store(false, Ordering::SeqCst);
+                if let Some(first) = codes.first() {
+                    self.set_status(OperationStatus::Deleting(first.clone()));
+                }
+            }
+            Command::CheckNeedsUpdate(oaci) => {
+                // Avoid queueing the same check several times
+                if !self.state().pending_checks.insert(oaci.clone()) {
+                    return;
+                }
+            }
+            Command::CheckAllUpdates => {
+                self.cancel_requested.store(false, Ordering::SeqCst);
+                let total = self
+                    .state()
+                    .entries
+                    .iter()
+                    .filter(|e| e.entry.available_locally)
+                    .count();
+                self.set_status(OperationStatus::CheckingUpdates { current: 0, total });
+            }
+            Command::ExportBriefing { .. } => {
+                self.set_status(OperationStatus::Exporting("briefing pack".to_string()));
+            }
+            Command::ExportArchive { .. } => {
+                self.set_status(OperationStatus::Exporting("archive".to_string()));
+            }
+            Command::SyncMirrors => {
+                self.set_status(OperationStatus::Mirroring(String::new()));
+            }
+            Command::RestoreEdition { oaci, .. } => {
+                self.set_status(OperationStatus::Restoring(oaci.clone()));
+            }
+            Command::CompareEdition { oaci, .. } => {
+                self.set_status(OperationStatus::Comparing(oaci.clone()));
+            }
+            Command::Relocate(..) => {
+                self.cancel_requested.store(false, Ordering::SeqCst);
+                self.set_status(OperationStatus::Relocating {
+                    current: 0,
+                    total: 0,
+                });
+            }
+        }
+
+        let core = self.clone();
+        thread::spawn(move || match command {
+            Command::FetchList => {
+                let _ = core.fetch_list();
+            }
+            Command::Download(codes) => {
+                let _ = core.download(codes.as_deref(), false);
+            }
+            Command::Redownload(codes) => {
+                let _ = core.redownload(&codes, false);
+            }
+            Command::DownloadAnyway(shortfall) => {
+                let _ = core.download_with(shortfall.codes.as_deref(), shortfall.force, true);
+            }
+            Command::VerifyLibrary => {
+                let _ = core.verify_library();
+            }
+            Command::AdoptFiles(files) => {
+                core.adopt_files(&files);
+            }
+            Command::RestoreTrash(ids) => {
+                core.restore_from_trash(&ids);
+            }
+            Command::Delete(codes) => {
+                core.delete(&codes);
+            }
+            Command::CheckNeedsUpdate(oaci) => {
+                let _ = core.check_needs_update(&oaci);
+            }
+            Command::CheckAllUpdates => {
+                core.check_all_updates();
+            }
+            Command::Relocate(config, mode) => {
+                let _ = core.relocate(&config, mode);
+            }
+            Command::ExportBriefing {
+                codes,
+                cover,
+                output,
+            } => {
+                let _ = core.export_briefing(&codes, cover, &output);
+            }
+            Command::ExportArchive {
+                codes,
+                name_template,
+                output,
+            } => {
+                let _ = core.export_archive(&codes, &name_template, &output);
+            }
+            Command::SyncMirrors => match core.sync_mirrors() {
+                Ok(changes) => core.set_status(OperationStatus::Completed(format!(
+                    "{} change(s) mirrored",
+                    changes
+                ))),
+                Err(e) => core.set_status(OperationStatus::Error(e.to_string())),
+            },
+            Command::RestoreEdition { oaci, file_name } => {
+                let _ = core.restore_edition(&oaci, &file_name);
+            }
+            Command::CompareEdition {
+                oaci,
+                file_name,
+                version,
+            } => {
+                let _ = core.compare_edition(&oaci, &file_name, &version);
+            }
+        });
+    }
+
+    /// Fetch the list of available charts, blocking until done
+    pub fn fetch_list(&self) -> Result<()> {
+        self.set_status(OperationStatus::FetchingList);
+
+        match self.reload_entries() {
+            Ok(()) => {
+                self.set_status(OperationStatus::Idle);
+                Ok(())
+            }
+            Err(e) => {
+                let message = format!("Failed to fetch list: {}", e);
+                self.set_status(OperationStatus::Error(message.clone()));
+                Err(message.into())
+            }
+        }
+    }
+
+    /// Download the given charts, or all of them, blocking until done
+    ///
+    /// Charts are fetched concurrently by the download engine while the cache
+    /// database is only written from this thread. The operation can be
+    /// cancelled; charts being downloaded at that time are completed.
+    ///
+    /// When the charts may not fit in the free disk space, nothing is downloaded
+    /// and [`Event::LowDiskSpace`] is sent, unless `ignore_space` or the
+    /// `ignore_low_disk_space` setting is set.
+    pub fn download(&self, codes: Option<&[String]>, ignore_space: bool) -> Result<SyncStats> {
+        self.download_with(codes, false, ignore_space)
+    }
+
+    /// Download the given charts again, blocking until done
+    ///
+    /// Unlike [`Core::download`], charts are downloaded even when their file
+    /// looks up to date, e.g. because it is not a valid PDF. The charts
+    /// downloaded are removed from the last verification report.
+    pub fn redownload(&self, codes: &[String], ignore_space: bool) -> Result<SyncStats> {
+        let stats = self.download_with(Some(codes), true, ignore_space)?;
+
+        let mut state = self.state();
+        let state = &mut *state;
+        if let (Some(verify_report), Some(report)) = (&mut state.verify_report, &state.report) {
+            verify_report
+                .problems
+                .retain(|(oaci, _)| !report.succeeded.contains(oaci));
+        }
+        Ok(stats)
+    }
+
+    fn download_with(
+        &self,
+        codes: Option<&[String]>,
+        force: bool,
+        ignore_space: bool,
+    ) -> Result<SyncStats> {
+        match self.run_download(codes, force, ignore_space) {
+            Ok(stats) => Ok(stats),
+            Err(e) => {
+                self.take_cancel_request();
+                let message = format!("Download failed: {}", e);
+                self.set_status(OperationStatus::Error(message.clone()));
+                Err(message.into())
+            }
+        }
+    }
+
+    fn run_download(
+        &self,
+        codes: Option<&[String]>,
+        force: bool,
+        ignore_space: bool,
+    ) -> Result<SyncStats> {
+        let config = self.config();
+        self.state().space_shortfall = None;
+        let entries = self.downloader.lock().unwrap().list_vacs(codes)?;
+        let database = VacDatabase::new(&config.database_path)?;
+        let engine = DownloadEngine::new(
+            &config.download_directory,
+            config.download_workers,
+            config.requests_per_second,
+            config.max_retries,
+        )?;
+        let download_dir = Path::new(&config.download_directory);
+
+        let mut stats = SyncStats {
+            total_entries: entries.len(),
+            ..Default::default()
+        };
+        let mut progress = DownloadProgress::new(entries.len());
+        let mut report = DownloadReport::default();
+
+        // Verify local charts and queue the ones that must be downloaded
+        let mut jobs = Vec::new();
+        for mut entry in entries {
+            if self.is_cancelling() {
+                break;
+            }
+            if force || engine::needs_download(&database, download_dir, &mut entry, &mut stats)? {
+                jobs.push(entry);
+            } else {
+                self.mark_up_to_date(&entry.oaci);
+                progress.up_to_date += 1;
+            }
+        }
+        stats.to_download = jobs.len();
+        report.up_to_date = stats.up_to_date;
+
+        // Make sure the charts fit on disk before writing anything
+        let required = engine::required_space(download_dir, &jobs, config.history_versions > 0);
+        match storage::free_space(download_dir) {
+            Some(available) if required > available => {
+                let shortfall = SpaceShortfall {
+                    required,
+                    available,
+                    codes: codes.map(<[String]>::to_vec),
+                    force,
+                };
+                if ignore_space || config.ignore_low_disk_space {
+                    eprintln!("⚠️  {}, downloading anyway", shortfall);
+                } else {
+                    self.state().space_shortfall = Some(shortfall.clone());
+                    self.emit(Event::LowDiskSpace(shortfall.clone()));
+                    return Err(shortfall.to_string().into());
+                }
+            }
+            Some(_) => {}
+            None => eprintln!("⚠️  Free disk space unknown, skipping the space check"),
+        }
+
+        if config.history_versions > 0 {
+            self.archive_editions(&database, download_dir, &jobs, config.history_versions);
+        }
+        self.set_status(OperationStatus::Downloading(progress.clone()));
+
+        let mut disk_full = false;
+        engine.run(jobs, &self.cancel_requested, |event| {
+            match event {
+                EngineEvent::Started(oaci) => {
+                    progress.started.get_or_insert_with(Instant::now);
+                    progress.oaci = Some(oaci);
+                }
+                EngineEvent::Retrying {
+                    oaci,
+                    attempt,
+                    error,
+                } => {
+                    eprintln!("  ⚠️  Attempt {} for {} failed: {}", attempt, oaci, error);
+                }
+                EngineEvent::Finished {
+                    entry,
+                    result,
+                    attempts,
+                } => {
+                    // A chart given up on cancel request is counted as skipped
+                    if result.as_ref().is_err_and(|e| e.is_cancelled()) {
+                        println!("  ⏭️  Skipped {}", entry.oaci);
+                        self.set_status(OperationStatus::Downloading(progress.clone()));
+                        return Ok(());
+                    }
+                    if attempts > 1 {
+                        report.retried.push((entry.oaci.clone(), attempts));
+                    }
+                    match result {
+                        Ok(bytes) => {
+                            // Update cache
+                            database.upsert_entry(&entry)?;
+                            self.mark_up_to_date(&entry.oaci);
+                            stats.downloaded += 1;
+                            progress.bytes += bytes;
+                            report.succeeded.push(entry.oaci);
+                        }
+                        Err(e) => {
+                            eprintln!("  ✗ Failed to download {}: {}", entry.oaci, e);
+                            if e.is_storage_full() {
+                                // Let the charts being downloaded finish, but start no other
+                                disk_full = true;
+                                self.cancel_requested.store(true, Ordering::SeqCst);
+                            }
+                            stats.failed += 1;
+                            report.failed.push((entry.oaci, e.to_string()));
+                        }
+                    }
+                    progress.current += 1;
+                }
+            }
+            self.set_status(OperationStatus::Downloading(progress.clone()));
+            Ok(())
+        })?;
+
+        let cancelled = self.take_cancel_request() && progress.done() < progress.total;
+
+        // Refresh the list to update local status
+        let _ = self.reload_entries();
+        let failed = report.failed.len();
+        self.state().report = Some(report);
+        let status = if disk_full {
+            OperationStatus::Error(format!(
+                "Disk full: download stopped after {} of {} charts",
+                progress.current,
+                progress.to_download()
+            ))
+        } else if cancelled {
+            OperationStatus::Cancelled {
+                completed: progress.done(),
+                skipped: progress.total - progress.done(),
+            }
+        } else if failed > 0 {
+            OperationStatus::Error(format!("{} chart(s) failed to download", failed))
+        } else {
+            OperationStatus::Idle
+        };
+        let mirrored = self.sync_mirrors().map(|_| ());
+        self.finish(mirrored, status);
+        Ok(stats)
+    }
+
+    /// Copy the local charts about to be replaced by a new version to the history
+    ///
+    /// A chart that cannot be archived is still updated.
+    fn archive_editions(
+        &self,
+        database: &VacDatabase,
+        download_dir: &Path,
+        jobs: &[VacEntry],
+        keep: usize,
+    ) {
+        let history_dir = self.config().history_directory();
+        for entry in jobs {
+            let cached = database
+                .get_cached_version(&entry.oaci, &entry.vac_type)
+                .ok()
+                .flatten()
+                .zip(database.get_file_name(&entry.oaci).ok().flatten());
+            let Some((version, file_name)) = cached else {
+                continue;
+            };
+            let chart = download_dir.join(file_name);
+            if version == entry.version || !chart.exists() {
+                continue;
+            }
+
+            let downloaded = self.state().download_dates.get(&entry.oaci).cloned();
+            let mut history = ChartHistory::open(&history_dir, &entry.oaci);
+            match history.archive(&chart, &version, downloaded, keep) {
+                Ok(()) => println!("  🗄️  Archived {} version {}", entry.oaci, version),
+                Err(e) => eprintln!("  ✗ Failed to archive {}: {}", entry.oaci, e),
+            }
+        }
+    }
+
+    /// Record that a chart is available locally and up to date
+    fn mark_up_to_date(&self, oaci: &str) {
+        let mut state = self.state();
+        state.needs_update.remove(oaci);
+        if let Some(entry) = state.entries.iter_mut().find(|e| e.entry.oaci == oaci) {
+            entry.entry.available_locally = true;
+        }
+    }
+
+    /// Delete the given local charts, blocking until done
+    ///
+    /// Failures do not stop the operation; the result of each deletion is returned.
+    /// Charts skipped because of a cancel request have no result.
+    pub fn delete(&self, codes: &[String]) -> Vec<(String, Result<Deletion>)> {
+        let total = codes.len();
+        let mut results = Vec::with_capacity(total);
+        let mut failed = Vec::new();
+        let mut trash = self.trash();
+        let mut trashed = Vec::new();
+
+        for (idx, oaci_code) in codes.iter().enumerate() {
+            if self.is_cancelling() {
+                break;
+            }
+
+            let label = if total == 1 {
+                oaci_code.clone()
+            } else {
+                format!("{} ({}/{})", oaci_code, idx + 1, total)
+            };
+            self.set_status(OperationStatus::Deleting(label));
+
+            // Keep the file in the trash so that the deletion can be undone
+            let result = self.trash_chart(&mut trash, oaci_code).and_then(|item| {
+                match self.downloader.lock().unwrap().delete(oaci_code) {
+                    Ok(result) => {
+                        let trash_id = item.map(|item| item.id);
+                        trashed.extend(trash_id.clone());
+                        Ok(Deletion {
+                            oaci: result.oaci,
+                            found: result.database_deleted,
+                            trash_id,
+                        })
+                    }
+                    Err(e) => {
+                        // The chart is still listed in the cache, put its file back
+                        if let Some(item) = item {
+                            let chart =
+                                Path::new(&self.config().download_directory).join(&item.file_name);
+                            if let Err(e) = trash.take_back(&item.id, &chart) {
+                                eprintln!("✗ Failed to restore {:?}: {}", chart, e);
+                            }
+                        }
+                        Err(e.into())
+                    }
+                }
+            });
+            match result {
+                Ok(result) => {
+                    // Update the local status in the list
+                    {
+                        let mut state = self.state();
+                        state.needs_update.remove(oaci_code);
+                        state.download_dates.remove(oaci_code);
+                        if let Some(entry) = state
+                            .entries
+                            .iter_mut()
+                            .find(|e| e.entry.oaci == *oaci_code)
+                        {
+                            entry.entry.available_locally = false;
+                            entry.selected = false; // Deselect after deletion
+                        }
+                    }
+                    self.emit(Event::Deleted(oaci_code.clone()));
+                    results.push((oaci_code.clone(), Ok(result)));
+                }
+                Err(e) => {
+                    eprintln!("Failed to delete {}: {}", oaci_code, e);
+                    failed.push(oaci_code.clone());
+                    results.push((oaci_code.clone(), Err(e)));
+                }
+            }
+        }
+
+        if let Err(e) = self.purge_expired_trash() {
+            eprintln!("Failed to empty the trash: {}", e);
+        }
+        let storage = self.measure_storage();
+        {
+            let mut state = self.state();
+            state.storage = storage;
+            state.last_trashed = trashed;
+        }
+        let cancelled = self.take_cancel_request() && results.len() < total;
+
+        let status = if !failed.is_empty() {
+            OperationStatus::Error(format!("Delete failed: {}", failed.join(", ")))
+        } else if cancelled {
+            OperationStatus::Cancelled {
+                completed: results.len(),
+                skipped: total - results.len(),
+            }
+        } else {
+            OperationStatus::Idle
+        };
+        let mirrored = self.sync_mirrors().map(|_| ());
+        self.finish(mirrored, status);
+
+        results
+    }
+
+    /// Move the file of a local chart to the trash, if it exists
+    fn trash_chart(&self, trash: &mut Trash, oaci: &str) -> Result<Option<TrashItem>> {
+        let config = self.config();
+        let database = VacDatabase::new(&config.database_path)?;
+        let Some(entry) = database
+            .get_all_entries()?
+            .into_iter()
+            .find(|e| e.oaci == oaci)
+        else {
+            return Ok(None);
+        };
+
+        let chart = Path::new(&config.download_directory).join(&entry.file_name);
+        if !chart.exists() {
+            return Ok(None);
+        }
+        let item = trash.put(&chart, &entry)?;
+        println!("🗑️  Moved {} to the trash", oaci);
+        Ok(Some(item))
+    }
+
+    /// Deleted charts that can still be restored
+    pub fn trash(&self) -> Trash {
+        Trash::open(&self.config().trash_directory())
+    }
+
+    /// Put deleted charts back in the library, blocking until done
+    ///
+    /// Failures do not stop the operation; the result of each item is returned.
+    pub fn restore_from_trash(&self, ids: &[String]) -> Vec<(String, Result<()>)> {
+        let config = self.config();
+        let mut trash = self.trash();
+        let mut results = Vec::with_capacity(ids.len());
+
+        match VacDatabase::new(&config.database_path) {
+            Ok(database) => {
+                for id in ids {
+                    let result = trash
+                        .restore(id, &database, Path::new(&config.download_directory))
+                        .map(|item| {
+                            println!("✓ Restored {} from the trash", item.oaci);
+                            self.set_status(OperationStatus::Restoring(item.oaci));
+                        });
+                    if let Err(e) = &result {
+                        eprintln!("✗ Failed to restore {}: {}", id, e);
+                    }
+                    results.push((id.clone(), result));
+                }
+            }
+            Err(e) => {
+                self.set_status(OperationStatus::Error(format!(
+                    "Failed to open database: {}",
+                    e
+                )));
+                return results;
+            }
+        }
+
+        let _ = self.reload_entries();
+        let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+        let status = if failed > 0 {
+            OperationStatus::Error(format!("{} chart(s) could not be restored", failed))
+        } else {
+            OperationStatus::Completed(format!("{} chart(s) restored", results.len()))
+        };
+        let mirrored = self.sync_mirrors().map(|_| ());
+        self.finish(mirrored, status);
+        results
+    }
+
+    /// Delete charts from the trash for good, returning how many were deleted
+    pub fn purge_trash(&self, ids: &[String]) -> Result<usize> {
+        let mut trash = self.trash();
+        for id in ids {
+            trash.purge(id)?;
+        }
+        Ok(ids.len())
+    }
+
+    /// Delete the charts that have been in the trash for longer than configured
+    pub fn purge_expired_trash(&self) -> Result<usize> {
+        let days = self.config().trash_days;
+        if days == 0 {
+            return Ok(0);
+        }
+        let purged = self.trash().purge_older_than(days)?;
+        if purged > 0 {
+            println!("🗑️  Emptied {} chart(s) from the trash", purged);
+        }
+        Ok(purged)
+    }
+
+    /// Check whether a local chart has a newer version available, blocking until done
+    pub fn check_needs_update(&self, oaci: &str) -> Result<bool> {
+        let result = self.downloader.lock().unwrap().needs_update(oaci);
+
+        // If we can't determine, assume it doesn't need update
+        let needs_update = *result.as_ref().unwrap_or(&false);
+        {
+            let mut state = self.state();
+            state.pending_checks.remove(oaci);
+            state.needs_update.insert(oaci.to_string(), needs_update);
+        }
+        self.emit(Event::NeedsUpdateChecked {
+            oaci: oaci.to_string(),
+            needs_update,
+        });
+
+        Ok(result?)
+    }
+
+    /// Check every local chart for a newer version, blocking until done
+    ///
+    /// Returns the OACI codes of the outdated charts.
+    pub fn check_all_updates(&self) -> Vec<String> {
+        let codes: Vec<String> = self
+            .state()
+            .entries
+            .iter()
+            .filter(|e| e.entry.available_locally)
+            .map(|e| e.entry.oaci.clone())
+            .collect();
+        let total = codes.len();
+        let mut completed = 0;
+
+        for oaci in &codes {
+            if self.is_cancelling() {
+                break;
+            }
+            self.set_status(OperationStatus::CheckingUpdates {
+                current: completed + 1,
+                total,
+            });
+            if let Err(e) = self.check_needs_update(oaci) {
+                eprintln!("Failed to check {} for updates: {}", oaci, e);
+            }
+            completed += 1;
+        }
+
+        if self.take_cancel_request() && completed < total {
+            self.set_status(OperationStatus::Cancelled {
+                completed,
+                skipped: total - completed,
+            });
+        } else {
+            self.set_status(OperationStatus::Idle);
+        }
+
+        let mut state = self.state();
+        state.sort();
+        state.outdated_codes()
+    }
+
+    /// Get the path of a local chart PDF
+    pub fn pdf_path(&self, oaci: &str) -> Result<PathBuf> {
+        Ok(self.downloader.lock().unwrap().get_pdf_path(oaci)?)
+    }
+
+    /// Previous editions of a chart kept in the history
+    pub fn chart_history(&self, oaci: &str) -> ChartHistory {
+        ChartHistory::open(&self.config().history_directory(), oaci)
+    }
+
+    /// Compare the drawing and the text of a previous edition of a chart with
+    /// the current one, blocking until done
+    ///
+    /// The outcome is kept in [`State::comparison`] for the front-end to show.
+    pub fn compare_edition(&self, oaci: &str, file_name: &str, version: &str) -> Result<()> {
+        self.set_status(OperationStatus::Comparing(oaci.to_string()));
+
+        let result = self
+            .compare_with_current(oaci, file_name)
+            .map_err(|e| e.to_string());
+        self.set_status(match &result {
+            Ok(_) => OperationStatus::Idle,
+            Err(e) => OperationStatus::Error(format!("Failed to compare {}: {}", oaci, e)),
+        });
+        let outcome = result.as_ref().map(|_| ()).map_err(|e| e.clone().into());
+        self.state().comparison = Some(EditionComparison {
+            oaci: oaci.to_string(),
+            version: version.to_string(),
+            result,
+        });
+        outcome
+    }
+
+    fn compare_with_current(&self, oaci: &str, file_name: &str) -> Result<ChartComparison> {
+        let history = self.chart_history(oaci);
+        let edition = history
+            .editions()
+            .iter()
+            .find(|e| e.file_name == file_name)
+            .ok_or_else(|| format!("No edition {} in the history", file_name))?;
+        compare::compare_editions(&history.path(edition), &self.pdf_path(oaci)?)
+    }
+
+    /// Put back a previous edition of a chart, blocking until done
+    ///
+    /// The current edition is archived first, so a restore can be undone by
+    /// restoring it in turn. The chart is then reported as outdated by the
+    /// next update check.
+    pub fn restore_edition(&self, oaci: &str, file_name: &str) -> Result<()> {
+        self.set_status(OperationStatus::Restoring(oaci.to_string()));
+
+        let result = self.run_restore(oaci, file_name);
+        let _ = self.reload_entries();
+        let status = match &result {
+            Ok(version) => {
+                println!("✓ Restored {} version {}", oaci, version);
+                OperationStatus::Completed(format!("Restored {} version {}", oaci, version))
+            }
+            Err(e) => OperationStatus::Error(format!("Failed to restore {}: {}", oaci, e)),
+        };
+        let mirrored = self.sync_mirrors().map(|_| ());
+        self.finish(mirrored, status);
+
+        result.map(|_| ())
+    }
+
+    fn run_restore(&self, oaci: &str, file_name: &str) -> Result<String> {
+        let config = self.config();
+        let database = VacDatabase::new(&config.database_path)?;
+        let mut entry = database
+            .get_all_entries()?
+            .into_iter()
+            .find(|e| e.oaci == oaci)
+            .ok_or_else(|| format!("{} is not available locally", oaci))?;
+        let chart = Path::new(&config.download_directory).join(&entry.file_name);
+
+        let mut history = self.chart_history(oaci);
+        let edition = history
+            .editions()
+            .iter()
+            .find(|e| e.file_name == file_name)
+            .cloned()
+            .ok_or_else(|| format!("No edition {} in the history", file_name))?;
+
+        if chart.exists() {
+            let downloaded = self.state().download_dates.get(oaci).cloned();
+            let keep = config.history_versions.max(history.editions().len() + 1);
+            history.archive(&chart, &entry.version, downloaded, keep)?;
+        }
+
+        let part = chart.with_extension("pdf.part");
+        fs::copy(history.path(&edition), &part)
+            .and_then(|_| fs::rename(&part, &chart))
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&part);
+            })?;
+        history.take(file_name)?;
+        history.purge(&edition);
+
+        entry.version = edition.version.clone();
+        entry.file_hash = Some(engine::file_hash(&chart)?);
+        database.upsert_entry(&entry)?;
+        self.state().needs_update.remove(oaci);
+
+        Ok(edition.version)
+    }
+
+    /// Merge local charts into a single PDF booklet, blocking until done
+    ///
+    /// Charts are merged in the order of `codes`; those not available locally
+    /// are skipped. Returns the number of pages written.
+    pub fn export_briefing(&self, codes: &[String], cover: bool, output: &Path) -> Result<usize> {
+        self.set_status(OperationStatus::Exporting("briefing pack".to_string()));
+
+        let result = self.local_charts(codes).and_then(|charts| {
+            if charts.is_empty() {
+                return Err("No local chart to export".into());
+            }
+            briefing::export_briefing(&charts, cover, output)
+        });
+
+        match &result {
+            Ok(pages) => {
+                println!("✓ Briefing pack written to {}", output.display());
+                self.set_status(OperationStatus::Completed(format!(
+                    "Briefing pack exported ({} pages): {}",
+                    pages,
+                    output.display()
+                )));
+            }
+            Err(e) => {
+                self.set_status(OperationStatus::Error(format!(
+                    "Failed to export briefing pack: {}",
+                    e
+                )));
+            }
+        }
+
+        result
+    }
+
+    /// Changes needed to bring each mirror target in line with the library
+    pub fn mirror_plans(&self) -> Result<Vec<MirrorPlan>> {
+        let config = self.config();
+        let database = VacDatabase::new(&config.database_path)?;
+        config
+            .mirror_directories
+            .iter()
+            .map(|target| {
+                MirrorPlan::new(
+                    &database,
+                    Path::new(&config.download_directory),
+                    Path::new(target),
+                )
+            })
+            .collect()
+    }
+
+    /// Copy the library changes to every mirror target, blocking until done
+    ///
+    /// All targets are synced even if one fails. Returns the number of files
+    /// copied or removed; the status is left for the caller to set.
+    pub fn sync_mirrors(&self) -> Result<usize> {
+        let config = self.config();
+        let mut changes = 0;
+        let mut errors = Vec::new();
+
+        for plan in self.mirror_plans()? {
+            if !plan.found {
+                println!("⚠️  Mirror folder {:?} not found, skipped", plan.target);
+                continue;
+            }
+            if plan
+                .actions
+                .iter()
+                .all(|a| matches!(a, MirrorAction::Conflict(_)))
+            {
+                continue;
+            }
+            self.set_status(OperationStatus::Mirroring(
+                plan.target.display().to_string(),
+            ));
+            match plan.apply(Path::new(&config.download_directory)) {
+                Ok(count) => {
+                    println!("✓ Mirrored {} change(s) to {:?}", count, plan.target);
+                    changes += count;
+                }
+                Err(e) => {
+                    eprintln!("✗ {}", e);
+                    errors.push(e.to_string());
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(changes)
+        } else {
+            Err(format!("Mirror sync failed: {}", errors.join("; ")).into())
+        }
+    }
+
+    /// Set the final status of an operation, reporting a mirror sync error
+    /// unless the operation already failed or was cancelled
+    fn finish(&self, mirrored: Result<()>, status: OperationStatus) {
+        match mirrored {
+            Err(e) if status == OperationStatus::Idle => {
+                self.set_status(OperationStatus::Error(e.to_string()))
+            }
+            _ => self.set_status(status),
+        }
+    }
+
+    /// Change the configuration and save it
+    ///
+    /// Only settings read at the start of each operation can be changed this
+    /// way; the download location is changed with [`Core::relocate`].
+    pub fn update_config<F: FnOnce(&mut Config)>(&self, update: F) -> Result<()> {
+        let mut config = self.config();
+        update(&mut config);
+        config.save()?;
+        *self.config.lock().unwrap() = config;
+        Ok(())
+    }
+
+    /// Start syncing in the background according to the configured schedule
+    ///
+    /// The scheduler runs until the process exits and skips its turn while
+    /// another operation is running.
+    pub fn start_scheduler(&self) {
+        let core = self.clone();
+        thread::spawn(move || {
+            let mut last_attempt: Option<Instant> = None;
+            loop {
+                thread::sleep(SCHEDULER_TICK);
+                let may_retry =
+                    last_attempt.is_none_or(|attempt| attempt.elapsed() >= SCHEDULER_RETRY_DELAY);
+                if may_retry && !core.is_busy() && core.is_sync_due(Local::now()) {
+                    last_attempt = Some(Instant::now());
+                    if let Err(e) = core.scheduled_sync() {
+                        eprintln!("✗ Scheduled sync failed: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Whether a background sync is due at `now`
+    pub fn is_sync_due(&self, now: DateTime<Local>) -> bool {
+        let config = self.config();
+        let last_sync = self.state().last_sync;
+        match config.sync_schedule {
+            SyncSchedule::Off => false,
+            SyncSchedule::Interval => {
+                let interval = chrono::Duration::hours(config.sync_interval_hours.max(1).into());
+                last_sync.is_none_or(|last| now - last >= interval)
+            }
+            SyncSchedule::Airac => {
+                let cycle = AiracCycle::at(now.date_naive());
+                last_sync.is_none_or(|last| last.date_naive() < cycle.effective)
+            }
+        }
+    }
+
+    /// Refresh the chart list and check local charts for updates, downloading
+    /// them when auto-update is enabled, blocking until done
+    pub fn scheduled_sync(&self) -> Result<()> {
+        println!("🕑 Scheduled sync");
+        self.fetch_list()?;
+        let outdated = self.check_all_updates();
+
+        if self.config().auto_update && !outdated.is_empty() {
+            let stats = self.download(Some(&outdated), false)?;
+            if stats.failed > 0 {
+                return Err(format!("{} chart(s) failed to update", stats.failed).into());
+            }
+        }
+
+        self.state().last_sync = Some(Local::now());
+        Ok(())
+    }
+
+    /// Write local charts to a ZIP archive with a manifest, blocking until done
+    ///
+    /// Charts not available locally are skipped. Returns the number of charts written.
+    pub fn export_archive(
+        &self,
+        codes: &[String],
+        name_template: &str,
+        output: &Path,
+    ) -> Result<usize> {
+        self.set_status(OperationStatus::Exporting("archive".to_string()));
+
+        let result = self.local_charts(codes).and_then(|charts| {
+            if charts.is_empty() {
+                return Err("No local chart to export".into());
+            }
+            archive::export_zip(&charts, name_template, output)
+        });
+
+        match &result {
+            Ok(count) => {
+                println!("✓ Archive written to {}", output.display());
+                self.set_status(OperationStatus::Completed(format!(
+                    "{} chart(s) exported: {}",
+                    count,
+                    output.display()
+                )));
+            }
+            Err(e) => {
+                self.set_status(OperationStatus::Error(format!(
+                    "Failed to export archive: {}",
+                    e
+                )));
+            }
+        }
+
+        result
+    }
+
+    /// Local charts among `codes`, in the same order
+    fn local_charts(&self, codes: &[String]) -> Result<Vec<LocalChart>> {
+        let entries: Vec<_> = {
+            let state = self.state();
+            codes
+                .iter()
+                .filter_map(|code| {
+                    state
+                        .entries
+                        .iter()
+                        .find(|e| e.entry.oaci == *code && e.entry.available_locally)
+                        .map(|e| e.entry.clone())
+                })
+                .collect()
+        };
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                Ok(LocalChart {
+                    path: self.pdf_path(&entry.oaci)?,
+                    oaci: entry.oaci,
+                    city: entry.city,
+                    version: entry.version,
+                })
+            })
+            .collect()
+    }
+
+    /// Move or copy the local charts to the download directory of `config`,
+    /// then switch to it and save the configuration, blocking until done
+    ///
+    /// On failure or cancel request, the current directory and cache are kept.
+    pub fn relocate(&self, config: &Config, mode: RelocationMode) -> Result<RelocationReport> {
+        let current = self.config();
+        let result = VacDatabase::new(&current.database_path)
+            .map_err(|e| e.into())
+            .and_then(|database| {
+                let cache = CacheDatabase::open(&current.database_path)?;
+                library::relocate(
+                    &database,
+                    &cache,
+                    Path::new(&current.download_directory),
+                    Path::new(&config.download_directory),
+                    mode,
+                    &self.cancel_requested,
+                    |current, total| {
+                        self.set_status(OperationStatus::Relocating { current, total })
+                    },
+                )
+            })
+            .and_then(|report| {
+                // The old files are only removed once the new directory is in use
+                config.save()?;
+                self.switch_config(config)?;
+                report.remove_sources();
+                Ok(report)
+            });
+
+        let cancelled = self.take_cancel_request();
+        match &result {
+            Ok(report) => {
+                println!(
+                    "✓ Relocated {} chart(s) to {}",
+                    report.transferred, config.download_directory
+                );
+                let _ = self.fetch_list();
+            }
+            Err(_) if cancelled => {
+                let total = self
+                    .state()
+                    .entries
+                    .iter()
+                    .filter(|e| e.entry.available_locally)
+                    .count();
+                self.set_status(OperationStatus::Cancelled {
+                    completed: 0,
+                    skipped: total,
+                });
+            }
+            Err(e) => {
+                self.set_status(OperationStatus::Error(e.to_string()));
+            }
+        }
+
+        result
+    }
+
+    /// Check every local chart for a missing, truncated or corrupted file,
+    /// blocking until done
+    ///
+    /// The report is kept in the state for the front-end to show.
+    pub fn verify_library(&self) -> Result<VerifyReport> {
+        let config = self.config();
+        let mut charts = 0;
+        let result = VacDatabase::new(&config.database_path)
+            .map_err(|e| e.into())
+            .and_then(|database| {
+                let cache = CacheDatabase::open(&config.database_path)?;
+                library::verify(
+                    &database,
+                    &cache,
+                    Path::new(&config.download_directory),
+                    &self.cancel_requested,
+                    |current, total| {
+                        charts = total;
+                        self.set_status(OperationStatus::Verifying { current, total })
+                    },
+                )
+            });
+
+        let cancelled = self.take_cancel_request();
+        match &result {
+            Ok(report) => {
+                println!(
+                    "✓ Verified {} chart(s), {} problem(s) found",
+                    report.checked,
+                    report.problems.len()
+                );
+                self.state().verify_report = Some(report.clone());
+                self.set_status(if cancelled {
+                    OperationStatus::Cancelled {
+                        completed: report.checked,
+                        skipped: charts - report.checked,
+                    }
+                } else if report.problems.is_empty() {
+                    OperationStatus::Completed(format!(
+                        "{} chart(s) verified, no problem found",
+                        report.checked
+                    ))
+                } else {
+                    OperationStatus::Error(format!(
+                        "{} damaged or missing chart(s) found",
+                        report.problems.len()
+                    ))
+                });
+            }
+            Err(e) => {
+                self.set_status(OperationStatus::Error(format!(
+                    "Failed to verify library: {}",
+                    e
+                )));
+            }
+        }
+
+        result
+    }
+
+    /// PDF files of the download directory unknown to the cache, matched to
+    /// the charts of the list when possible
+    pub fn foreign_files(&self) -> Result<Vec<ForeignFile>> {
+        let config = self.config();
+        let database = VacDatabase::new(&config.database_path)?;
+        let entries: Vec<VacEntry> = self
+            .state()
+            .entries
+            .iter()
+            .map(|e| e.entry.clone())
+            .collect();
+        library::scan_foreign(&database, Path::new(&config.download_directory), &entries)
+    }
+
+    /// Add foreign files to the cache as the local copies of the given charts,
+    /// blocking until done
+    ///
+    /// Failures do not stop the operation; the result of each file is returned.
+    pub fn adopt_files(&self, files: &[(String, String)]) -> Vec<(String, Result<()>)> {
+        let config = self.config();
+        let download_dir = Path::new(&config.download_directory);
+        let mut results = Vec::with_capacity(files.len());
+
+        match VacDatabase::new(&config.database_path) {
+            Ok(database) => {
+                for (file_name, oaci) in files {
+                    self.set_status(OperationStatus::Adopting(file_name.clone()));
+                    let entry = self
+                        .state()
+                        .entries
+                        .iter()
+                        .find(|e| e.entry.oaci == *oaci)
+                        .map(|e| e.entry.clone());
+                    let result = match entry {
+                        Some(entry) => library::adopt(&database, download_dir, file_name, &entry),
+                        None => Err(format!("Unknown OACI code {}", oaci).into()),
+                    };
+                    match &result {
+                        Ok(()) => println!("✓ Adopted {} as {}", file_name, oaci),
+                        Err(e) => eprintln!("✗ Failed to adopt {}: {}", file_name, e),
+                    }
+                    results.push((file_name.clone(), result));
+                }
+            }
+            Err(e) => {
+                self.set_status(OperationStatus::Error(format!(
+                    "Failed to open database: {}",
+                    e
+                )));
+                return results;
+            }
+        }
+
+        let _ = self.reload_entries();
+        let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+        let status = if failed > 0 {
+            OperationStatus::Error(format!("{} file(s) could not be adopted", failed))
+        } else {
+            OperationStatus::Completed(format!("{} file(s) adopted", results.len()))
+        };
+        let mirrored = self.sync_mirrors().map(|_| ());
+        self.finish(mirrored, status);
+        results
+    }
+
+    /// Delete foreign files from the download directory
+    ///
+    /// Files known to the cache are never deleted. Returns the number of
+    /// files deleted.
+    pub fn delete_foreign_files(&self, file_names: &[String]) -> Result<usize> {
+        let download_dir = PathBuf::from(&self.config().download_directory);
+        let mut deleted = 0;
+
+        for file in self.foreign_files()? {
+            if file_names.contains(&file.file_name) {
+                fs::remove_file(download_dir.join(&file.file_name))?;
+                println!("🗑️  Deleted {}", file.file_name);
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Switch to a new configuration, discarding the cache database
+    ///
+    /// The charts of the previous download directory are forgotten and have to
+    /// be downloaded again.
+    pub fn reset(&self, config: &Config) -> Result<()> {
+        // Delete the old database file to reset the cache
+        if std::path::Path::new(&config.database_path).exists() {
+            match std::fs::remove_file(&config.database_path) {
+                Ok(_) => println!("Deleted old database cache"),
+                Err(e) => println!("Warning: Could not delete old database: {}", e),
+            }
+        }
+
+        // Reinitialize VacDownloader with new paths (creates fresh database)
+        self.switch_config(config)?;
+        println!("Fresh database created");
+
+        Ok(())
+    }
+
+    /// Reinitialize the downloader with the paths of a new configuration
+    fn switch_config(&self, config: &Config) -> Result<()> {
+        let new_downloader = VacDownloader::new(&config.database_path, &config.download_directory)?;
+        *self.downloader.lock().unwrap() = new_downloader;
+        *self.config.lock().unwrap() = config.clone();
+        self.state().needs_update.clear();
+        println!("VacDownloader reinitialized with new download location");
+        Ok(())
+    }
+
+    /// Measure the disk usage of the local charts, empty if it can't be measured
+    fn measure_storage(&self) -> StorageUsage {
+        let config = self.config();
+        VacDatabase::new(&config.database_path)
+            .map_err(|e| e.into())
+            .and_then(|database| {
+                StorageUsage::measure(&database, Path::new(&config.download_directory))
+            })
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to measure storage usage: {}", e);
+                StorageUsage::default()
+            })
+    }
+
+    /// Reload the chart list from the downloader, keeping the current sort order
+    /// and selection
+    fn reload_entries(&self) -> Result<()> {
+        let vacs = self.downloader.lock().unwrap().list_vacs(None)?;
+        let database_path = self.config.lock().unwrap().database_path.clone();
+        let storage = self.measure_storage();
+        let download_dates = CacheDatabase::open(&database_path)
+            .and_then(|db| db.download_dates())
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to read download dates: {}", e);
+                HashMap::new()
+            });
+
+        {
+            let mut state = self.state();
+            let mut selected: HashSet<String> = state.selected_codes().into_iter().collect();
+            selected.extend(std::mem::take(&mut state.pending_selection));
+            state.entries = vacs
+                .into_iter()
+                .map(|vac| {
+                    let mut entry = VacEntryWithSelection::new(vac);
+                    entry.selected = selected.contains(&entry.entry.oaci);
+                    entry
+                })
+                .collect();
+            state.download_dates = download_dates;
+            state.storage = storage;
+            state.sort();
+        }
+        self.emit(Event::ListUpdated);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use vac_downloader::VacEntry;
+
+    fn chart(oaci: &str, city: &str, local: bool) -> VacEntryWithSelection {
+        VacEntryWithSelection::new(VacEntry {
+            city: city.to_string(),
+            file_size: 1000,
+            available_locally: local,
+            ..testing::entry(oaci)
+        })
+    }
+
+    /// LFAA not downloaded, LFBB outdated, LFCC not checked, LFDD up to date
+    fn state() -> State {
+        let mut state = State::new();
+        state.entries = vec![
+            chart("LFDD", "Dijon", true),
+            chart("LFCC", "Cahors", true),
+            chart("LFBB", "Bordeaux", true),
+            chart("LFAA", "Amiens", false),
+        ];
+        state.needs_update.insert("LFBB".to_string(), true);
+        state.needs_update.insert("LFDD".to_string(), false);
+        state
+    }
+
+    fn codes(state: &State) -> Vec<&str> {
+        state
+            .entries
+            .iter()
+            .map(|e| e.entry.oaci.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_by_update_status() {
+        let mut state = state();
+        state.sort_column = SortColumn::Update;
+        state.sort();
+        assert_eq!(codes(&state), ["LFBB", "LFCC", "LFDD", "LFAA"]);
+
+        state.sort_ascending = false;
+        state.sort();
+        assert_eq!(codes(&state), ["LFAA", "LFDD", "LFCC", "LFBB"]);
+    }
+
+    #[test]
+    fn sorts_by_local_status_then_code() {
+        let mut state = state();
+        state.sort_column = SortColumn::Status;
+        state.sort();
+        assert_eq!(codes(&state), ["LFAA", "LFBB", "LFCC", "LFDD"]);
+    }
+
+    #[test]
+    fn filters_by_search_and_status() {
+        let mut state = state();
+        state.sort();
+        assert_eq!(state.filtered_indices("bord", StatusFilter::All), [1]);
+        assert_eq!(state.filtered_indices("", StatusFilter::Outdated), [1]);
+        assert_eq!(state.filtered_indices("", StatusFilter::NotDownloaded), [0]);
+        assert_eq!(
+            state.filtered_indices("lf", StatusFilter::Downloaded),
+            [1, 2, 3]
+        );
+
+        state.entries[3].selected = true;
+        assert_eq!(state.filtered_indices("", StatusFilter::Selected), [3]);
+        assert_eq!(state.outdated_codes(), ["LFBB"]);
+    }
+
+    #[test]
+    fn selection_is_kept_until_the_list_is_loaded() {
+        let mut loading = State::new();
+        loading.pending_selection = ["LFRS".to_string(), "LFPN".to_string()].into();
+        assert_eq!(loading.selection_to_save(), ["LFPN", "LFRS"]);
+
+        let mut state = state();
+        state.entries[1].selected = true;
+        assert_eq!(state.selection_to_save(), ["LFCC"]);
+    }
+
+    #[test]
+    fn previous_cycle_uses_the_download_date() {
+        let mut state = state();
+        let cycle = AiracCycle::at(NaiveDate::from_ymd_opt(2025, 1, 23).unwrap());
+        state
+            .download_dates
+            .insert("LFBB".to_string(), "2025-01-22 23:59:59".to_string());
+        state
+            .download_dates
+            .insert("LFCC".to_string(), "2025-01-23 00:00:00".to_string());
+
+        assert!(state.predates_cycle(&state.entries[2], &cycle));
+        assert!(!state.predates_cycle(&state.entries[1], &cycle));
+        assert!(!state.predates_cycle(&state.entries[0], &cycle));
+    }
+
+    #[test]
+    fn download_estimate_counts_missing_and_outdated_charts() {
+        let mut state = state();
+        state.storage.sizes.insert("LFBB".to_string(), 400);
+        assert_eq!(state.download_estimate(), 1000 + 600);
+    }
+
+    #[test]
+    fn deleted_charts_are_reported_with_their_trash_item() {
+        let (dir, _database, downloads) = testing::library(&["LFAA"]);
+        let config = Config {
+            database_path: dir
+                .path()
+                .join(testing::DATABASE_NAME)
+                .display()
+                .to_string(),
+            download_directory: downloads.display().to_string(),
+            ..Config::default()
+        };
+        let core = Core::new(&config).unwrap();
+
+        let results = core.delete(&["LFAA".to_string(), "LFZZ".to_string()]);
+        let deletions: Vec<_> = results.into_iter().map(|(_, r)| r.unwrap()).collect();
+        let id = core.trash().items()[0].id.clone();
+        assert_eq!(deletions[0].trash_id.as_ref(), Some(&id));
+        assert!(deletions[0].found);
+        assert!(!deletions[1].found && deletions[1].trash_id.is_none());
+
+        assert!(core.restore_from_trash(&[id])[0].1.is_ok());
+        assert!(downloads.join("LFAA.pdf").exists());
+    }
+}
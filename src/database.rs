@@ -0,0 +1,56 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use rusqlite::{Connection, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Access to the columns of the vac_downloader cache that
+/// `vac_downloader::VacDatabase` does not expose
+pub struct CacheDatabase {
+    conn: Connection,
+}
+
+impl CacheDatabase {
+    /// Open the cache database created by `vac_downloader`
+    pub fn open<P: AsRef<Path>>(db_path: P) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+        Ok(Self { conn })
+    }
+
+    /// Date each chart was last downloaded ("YYYY-MM-DD HH:MM:SS", UTC), by OACI code
+    pub fn download_dates(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT oaci, last_updated FROM vac_cache WHERE last_updated IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    /// Record the checksum of a chart without changing its download date
+    pub fn set_file_hash(&self, oaci: &str, vac_type: &str, hash: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE vac_cache SET file_hash = ?1 WHERE oaci = ?2 AND vac_type = ?3",
+            (hash, oaci, vac_type),
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use reqwest::blocking::Client;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+use vac_downloader::downloader::SyncStats;
+use vac_downloader::{AuthGenerator, VacDatabase, VacEntry};
+
+const API_HOST: &str = "bo-prod-sofia-vac.sia-france.fr";
+const FILE_ENDPOINT: &str = "/api/v1/custom/file-path";
+
+/// Delay before the first retry, doubled after each failed attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Progress notifications sent by the workers to the calling thread
+pub enum EngineEvent {
+    /// A worker started downloading a chart
+    Started(String),
+    /// A transient error occurred, the chart will be downloaded again
+    Retrying {
+        oaci: String,
+        attempt: u32,
+        error: String,
+    },
+    /// A chart download finished; on success the entry carries the file hash
+    /// and the number of bytes written is returned
+    Finished {
+        entry: VacEntry,
+        result: std::result::Result<u64, ChartError>,
+        attempts: u32,
+    },
+}
+
+/// Error raised while downloading a chart
+#[derive(Debug)]
+pub struct ChartError {
+    message: String,
+    /// Whether trying again later may succeed (network error, server overload)
+    transient: bool,
+    /// Whether the disk is full, so that no other chart can be written
+    storage_full: bool,
+    /// Whether the download was given up on cancel request, before it failed
+    cancelled: bool,
+}
+
+impl ChartError {
+    fn transient(message: String) -> Self {
+        Self {
+            message,
+            transient: true,
+            storage_full: false,
+            cancelled: false,
+        }
+    }
+
+    fn permanent(message: String) -> Self {
+        Self {
+            message,
+            transient: false,
+            storage_full: false,
+            cancelled: false,
+        }
+    }
+
+    fn storage_full(message: String) -> Self {
+        Self {
+            message,
+            transient: false,
+            storage_full: true,
+            cancelled: false,
+        }
+    }
+
+    fn cancelled() -> Self {
+        Self {
+            message: "Download cancelled".to_string(),
+            transient: false,
+            storage_full: false,
+            cancelled: true,
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.transient
+    }
+
+    pub fn is_storage_full(&self) -> bool {
+        self.storage_full
+    }
+
+    /// Whether the chart was skipped on cancel request rather than failed
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl std::fmt::Display for ChartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ChartError {}
+
+/// Limits the rate of requests sent to each host
+pub struct RateLimiter {
+    interval: Option<Duration>,
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    /// Allow at most `requests_per_second` requests per host (0 for no limit)
+    pub fn new(requests_per_second: f64) -> Self {
+        let interval = if requests_per_second > 0.0 {
+            Some(Duration::from_secs_f64(1.0 / requests_per_second))
+        } else {
+            None
+        };
+
+        Self {
+            interval,
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Block until a request to `host` is allowed
+    pub fn acquire(&self, host: &str) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_slot.get(host).copied().unwrap_or(now).max(now);
+            next_slot.insert(host.to_string(), slot + interval);
+            slot
+        };
+
+        let now = Instant::now();
+        if slot > now {
+            thread::sleep(slot - now);
+        }
+    }
+}
+
+/// Downloads chart PDFs from the SIA server using several worker threads
+///
+/// The engine only writes PDF files; updating the cache database is left to
+/// the caller, which receives the results on a single thread.
+pub struct DownloadEngine {
+    client: Client,
+    download_dir: PathBuf,
+    workers: usize,
+    max_retries: u32,
+    limiter: RateLimiter,
+}
+
+impl DownloadEngine {
+    pub fn new<P: AsRef<Path>>(
+        download_dir: P,
+        workers: usize,
+        requests_per_second: f64,
+        max_retries: u32,
+    ) -> Result<Self> {
+        let download_dir = download_dir.as_ref().to_path_buf();
+        fs::create_dir_all(&download_dir)?;
+
+        let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+
+        Ok(Self {
+            client,
+            download_dir,
+            workers: workers.max(1),
+            max_retries,
+            limiter: RateLimiter::new(requests_per_second),
+        })
+    }
+
+    /// Download a single chart and return the file hash and size
+    ///
+    /// The PDF is written to a temporary file first so that a failed download
+    /// never leaves a truncated chart behind.
+    pub fn download_chart(
+        &self,
+        entry: &VacEntry,
+    ) -> std::result::Result<(String, u64), ChartError> {
+        let api_path = format!("{}/{}/{}", FILE_ENDPOINT, entry.oaci, entry.vac_type);
+        let url = format!("https://{}{}", API_HOST, api_path);
+
+        self.limiter.acquire(API_HOST);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("AUTH", AuthGenerator::generate_auth_header(&api_path, None))
+            .header("Authorization", AuthGenerator::generate_basic_auth())
+            .send()
+            .map_err(|e| ChartError::transient(format!("Request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = format!("PDF download failed with status: {}", status);
+            return Err(
+                if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    ChartError::transient(message)
+                } else {
+                    ChartError::permanent(message)
+                },
+            );
+        }
+
+        let bytes = response
+            .bytes()
+            .map_err(|e| ChartError::transient(format!("Failed to read PDF bytes: {}", e)))?;
+        let hash = format!("{:x}", Sha256::digest(&bytes));
+
+        let file_path = self.download_dir.join(&entry.file_name);
+        let part_path = self.download_dir.join(format!("{}.part", entry.file_name));
+        if let Err(e) =
+            fs::write(&part_path, &bytes).and_then(|_| fs::rename(&part_path, &file_path))
+        {
+            let _ = fs::remove_file(&part_path);
+            let message = format!("Failed to write PDF to {:?}: {}", file_path, e);
+            return Err(if e.kind() == ErrorKind::StorageFull {
+                ChartError::storage_full(message)
+            } else {
+                ChartError::permanent(message)
+            });
+        }
+
+        Ok((hash, bytes.len() as u64))
+    }
+
+    /// Download a chart, retrying transient errors with exponential backoff
+    ///
+    /// Returns the result of the last attempt and the number of attempts made.
+    /// A chart waiting for a retry when `cancel` is set is returned as cancelled.
+    fn download_with_retries(
+        &self,
+        entry: &mut VacEntry,
+        cancel: &AtomicBool,
+        tx: &mpsc::Sender<EngineEvent>,
+    ) -> (std::result::Result<u64, ChartError>, u32) {
+        let mut attempt = 1;
+        loop {
+            match self.download_chart(entry) {
+                Ok((hash, size)) => {
+                    entry.file_hash = Some(hash);
+                    return (Ok(size), attempt);
+                }
+                Err(e) if e.is_transient() && attempt <= self.max_retries => {
+                    let _ = tx.send(EngineEvent::Retrying {
+                        oaci: entry.oaci.clone(),
+                        attempt,
+                        error: e.to_string(),
+                    });
+
+                    // Wait before retrying, giving up early on cancel requests
+                    let deadline = Instant::now() + RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                    while Instant::now() < deadline {
+                        if cancel.load(Ordering::SeqCst) {
+                            return (Err(ChartError::cancelled()), attempt);
+                        }
+                        thread::sleep(Duration::from_millis(100));
+                    }
+                    attempt += 1;
+                }
+                Err(e) => return (Err(e), attempt),
+            }
+        }
+    }
+
+    /// Download all `jobs` concurrently, calling `on_event` on the calling thread
+    ///
+    /// A failed chart does not stop the other downloads.
+    /// Workers stop picking new charts once `cancel` is set. An error returned by
+    /// `on_event` also stops the workers and is returned once they have finished.
+    pub fn run<F>(&self, jobs: Vec<VacEntry>, cancel: &AtomicBool, mut on_event: F) -> Result<()>
+    where
+        F: FnMut(EngineEvent) -> Result<()>,
+    {
+        let queue = Mutex::new(VecDeque::from(jobs));
+        let stop = AtomicBool::new(false);
+        let (tx, rx) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..self.workers {
+                let tx = tx.clone();
+                let queue = &queue;
+                let stop = &stop;
+                scope.spawn(move || {
+                    loop {
+                        if stop.load(Ordering::SeqCst) || cancel.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        let Some(mut entry) = queue.lock().unwrap().pop_front() else {
+                            break;
+                        };
+
+                        let _ = tx.send(EngineEvent::Started(entry.oaci.clone()));
+                        let (result, attempts) =
+                            self.download_with_retries(&mut entry, cancel, &tx);
+                        let _ = tx.send(EngineEvent::Finished {
+                            entry,
+                            result,
+                            attempts,
+                        });
+                    }
+                });
+            }
+            drop(tx);
+
+            let mut result = Ok(());
+            for event in rx {
+                if result.is_ok() {
+                    result = on_event(event);
+                    if result.is_err() {
+                        stop.store(true, Ordering::SeqCst);
+                    }
+                }
+            }
+            result
+        })
+    }
+}
+
+/// Check whether a chart has to be downloaded, updating the statistics
+///
+/// Mirrors the checks done by `VacDownloader::sync`: a chart is downloaded when
+/// its version changed, or when the local file is missing or corrupted.
+pub fn needs_download(
+    database: &VacDatabase,
+    download_dir: &Path,
+    entry: &mut VacEntry,
+    stats: &mut SyncStats,
+) -> Result<bool> {
+    if database.needs_update(entry)? {
+        return Ok(true);
+    }
+
+    let file_path = download_dir.join(&entry.file_name);
+    if !file_path.exists() {
+        println!("  ⚠️  File missing for {} - redownloading", entry.oaci);
+        stats.redownloaded_corrupted += 1;
+        return Ok(true);
+    }
+
+    match file_hash(&file_path) {
+        Ok(current_hash) => match database.get_cached_hash(&entry.oaci, &entry.vac_type)? {
+            Some(cached_hash) if cached_hash != current_hash => {
+                println!(
+                    "  ⚠️  Hash mismatch for {} - file corrupted, redownloading",
+                    entry.oaci
+                );
+                stats.redownloaded_corrupted += 1;
+                return Ok(true);
+            }
+            Some(_) => {}
+            None => {
+                // No hash in database, calculate and store it
+                entry.file_hash = Some(current_hash);
+                database.upsert_entry(entry)?;
+            }
+        },
+        Err(e) => {
+            eprintln!("  ✗ Failed to calculate hash for {}: {}", entry.oaci, e);
+        }
+    }
+
+    stats.verified += 1;
+    stats.up_to_date += 1;
+    Ok(false)
+}
+
+/// Estimate the disk space needed to download `jobs` into `download_dir`
+///
+/// Uses the published file sizes. A chart replacing a local file only needs
+/// the difference, unless a copy of the previous file is kept in the history.
+pub fn required_space(download_dir: &Path, jobs: &[VacEntry], keep_previous: bool) -> u64 {
+    jobs.iter()
+        .map(|entry| {
+            let size = entry.file_size.max(0) as u64;
+            let existing = fs::metadata(download_dir.join(&entry.file_name))
+                .map(|metadata| metadata.len())
+                .unwrap_or(0);
+            if keep_previous {
+                size
+            } else {
+                size.saturating_sub(existing)
+            }
+        })
+        .sum()
+}
+
+/// Calculate the SHA-256 hash of a file
+pub fn file_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,225 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File listing the archived editions in the history folder of each chart
+pub const INDEX_FILE_NAME: &str = "history.toml";
+
+/// A previous edition of a chart kept in the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Edition {
+    /// Chart version, as published by the SIA
+    pub version: String,
+    /// Date the edition was downloaded ("YYYY-MM-DD HH:MM:SS", UTC), if known
+    pub downloaded: Option<String>,
+    /// Date the edition was replaced ("YYYY-MM-DD HH:MM:SS", UTC)
+    pub archived: String,
+    /// Name of the PDF file in the history folder of the chart
+    pub file_name: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryIndex {
+    #[serde(default)]
+    editions: Vec<Edition>,
+}
+
+/// Previous editions of one chart, newest first
+///
+/// Each chart has its own folder in the history directory, holding the
+/// archived PDFs and an index file with their versions and dates.
+#[derive(Debug, Clone)]
+pub struct ChartHistory {
+    pub oaci: String,
+    dir: PathBuf,
+    editions: Vec<Edition>,
+}
+
+impl ChartHistory {
+    /// Load the history of a chart; a chart without history has no editions
+    pub fn open(history_dir: &Path, oaci: &str) -> Self {
+        let dir = history_dir.join(oaci);
+        let editions = fs::read_to_string(dir.join(INDEX_FILE_NAME))
+            .ok()
+            .and_then(|contents| toml::from_str::<HistoryIndex>(&contents).ok())
+            .map(|index| index.editions)
+            .unwrap_or_default();
+
+        Self {
+            oaci: oaci.to_string(),
+            dir,
+            editions,
+        }
+    }
+
+    /// Archived editions, newest first
+    pub fn editions(&self) -> &[Edition] {
+        &self.editions
+    }
+
+    /// Path of the PDF file of an edition
+    pub fn path(&self, edition: &Edition) -> PathBuf {
+        self.dir.join(&edition.file_name)
+    }
+
+    /// Copy the current chart to the history and keep only the last `keep` editions
+    pub fn archive(
+        &mut self,
+        chart: &Path,
+        version: &str,
+        downloaded: Option<String>,
+        keep: usize,
+    ) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let now = Utc::now();
+        let file_name = format!(
+            "{}_{}_{}.pdf",
+            self.oaci,
+            sanitize(version),
+            now.format("%Y%m%d%H%M%S")
+        );
+        let part = self.dir.join(format!("{}.part", file_name));
+        fs::copy(chart, &part)
+            .and_then(|_| fs::rename(&part, self.dir.join(&file_name)))
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&part);
+            })?;
+
+        self.editions.insert(
+            0,
+            Edition {
+                version: version.to_string(),
+                downloaded,
+                archived: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+                file_name,
+            },
+        );
+        for edition in self.editions.split_off(keep.min(self.editions.len())) {
+            let _ = fs::remove_file(self.path(&edition));
+        }
+        self.save()
+    }
+
+    /// Remove an edition from the history, returning it
+    ///
+    /// The PDF file is left in place so that it can still be copied; call
+    /// [`ChartHistory::purge`] afterwards to delete it.
+    pub fn take(&mut self, file_name: &str) -> Result<Edition> {
+        let idx = self
+            .editions
+            .iter()
+            .position(|e| e.file_name == file_name)
+            .ok_or_else(|| format!("No edition {} in the history of {}", file_name, self.oaci))?;
+        let edition = self.editions.remove(idx);
+        self.save()?;
+        Ok(edition)
+    }
+
+    /// Delete the PDF file of an edition taken out of the history
+    pub fn purge(&self, edition: &Edition) {
+        let _ = fs::remove_file(self.path(edition));
+    }
+
+    fn save(&self) -> Result<()> {
+        let index = HistoryIndex {
+            editions: self.editions.clone(),
+        };
+        fs::write(
+            self.dir.join(INDEX_FILE_NAME),
+            toml::to_string_pretty(&index)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Make a version string usable in a file name
+fn sanitize(version: &str) -> String {
+    version
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn archive_keeps_the_last_editions() {
+        let dir = TempDir::new().unwrap();
+        let chart = dir.path().join("LFPN.pdf");
+        let history_dir = dir.path().join("history");
+        let mut history = ChartHistory::open(&history_dir, "LFPN");
+        assert!(history.editions().is_empty());
+
+        fs::write(&chart, "first").unwrap();
+        history.archive(&chart, "2025/01", None, 2).unwrap();
+        fs::write(&chart, "second").unwrap();
+        history.archive(&chart, "2025/02", None, 1).unwrap();
+
+        let reopened = ChartHistory::open(&history_dir, "LFPN");
+        assert_eq!(reopened.editions().len(), 1);
+        let edition = &reopened.editions()[0];
+        assert_eq!(edition.version, "2025/02");
+        assert!(edition.file_name.starts_with("LFPN_2025_02_"));
+        assert_eq!(
+            fs::read_to_string(reopened.path(edition)).unwrap(),
+            "second"
+        );
+        assert_eq!(
+            fs::read_dir(history_dir.join("LFPN")).unwrap().count(),
+            2,
+            "one PDF and the index"
+        );
+    }
+
+    #[test]
+    fn take_then_purge() {
+        let dir = TempDir::new().unwrap();
+        let chart = dir.path().join("LFPN.pdf");
+        fs::write(&chart, "chart").unwrap();
+        let mut history = ChartHistory::open(dir.path(), "LFPN");
+        history
+            .archive(&chart, "1", Some("2025-01-01 10:00:00".to_string()), 3)
+            .unwrap();
+        let file_name = history.editions()[0].file_name.clone();
+
+        let edition = history.take(&file_name).unwrap();
+        assert!(ChartHistory::open(dir.path(), "LFPN").editions().is_empty());
+        assert!(history.path(&edition).exists());
+        history.purge(&edition);
+        assert!(!history.path(&edition).exists());
+        assert!(history.take(&file_name).is_err());
+    }
+}
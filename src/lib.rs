@@ -0,0 +1,48 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Chart management for VAC Downloader, independent of any user interface.
+//!
+//! The [`core::Core`] service owns the chart list and the `VacDownloader`
+//! instance. Front-ends send it [`core::Command`]s and read its [`core::State`]
+//! or listen to its [`core::Event`]s.
+
+pub mod airac;
+pub mod archive;
+pub mod briefing;
+pub mod collections;
+pub mod compare;
+pub mod config;
+pub mod core;
+pub mod database;
+pub mod engine;
+pub mod history;
+pub mod library;
+pub mod mirror;
+pub mod models;
+pub mod render;
+pub mod route;
+pub mod storage;
+pub mod trash;
+pub mod ui_state;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,609 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use crate::database::CacheDatabase;
+use crate::engine::file_hash;
+use crate::models::format_bytes;
+use crate::storage::free_space;
+use lopdf::Document;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use vac_downloader::{VacDatabase, VacEntry};
+
+/// How local charts are transferred to a new download directory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelocationMode {
+    /// Copy the charts, then remove them from the old directory
+    Move,
+    /// Copy the charts and leave the old directory untouched
+    Copy,
+}
+
+/// Outcome of a successful relocation
+#[derive(Debug, Clone, Default)]
+pub struct RelocationReport {
+    /// Number of charts transferred to the new directory
+    pub transferred: usize,
+    /// Charts listed in the cache whose file was missing; they were removed from the cache
+    pub missing: Vec<String>,
+    /// Files of the old directory, removed by [`RelocationReport::remove_sources`]
+    pub sources: Vec<PathBuf>,
+}
+
+impl RelocationReport {
+    /// Remove the charts from the old directory, once the new one is in use
+    pub fn remove_sources(&self) {
+        for source in &self.sources {
+            if let Err(e) = fs::remove_file(source) {
+                eprintln!("  ✗ Failed to remove {:?}: {}", source, e);
+            }
+        }
+    }
+}
+
+/// Problem found with a local chart by [`verify`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartProblem {
+    /// The file listed in the cache does not exist
+    Missing,
+    /// The file ends before the end of the PDF, e.g. after an interrupted copy
+    Truncated,
+    /// The content differs from the checksum recorded at download time
+    Corrupted,
+    /// The file cannot be read as a PDF
+    Invalid(String),
+}
+
+impl Display for ChartProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChartProblem::Missing => write!(f, "File missing"),
+            ChartProblem::Truncated => write!(f, "File truncated"),
+            ChartProblem::Corrupted => write!(f, "Checksum mismatch"),
+            ChartProblem::Invalid(reason) => write!(f, "Invalid PDF: {}", reason),
+        }
+    }
+}
+
+/// Outcome of a library verification
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerifyReport {
+    /// Number of charts checked
+    pub checked: usize,
+    /// Charts with a problem, by OACI code
+    pub problems: Vec<(String, ChartProblem)>,
+    /// Charts without a checksum; the checksum of the valid file was recorded
+    pub recorded: Vec<String>,
+}
+
+impl VerifyReport {
+    /// OACI codes of the charts with a problem
+    pub fn problem_codes(&self) -> Vec<String> {
+        self.problems.iter().map(|(oaci, _)| oaci.clone()).collect()
+    }
+}
+
+/// Check that every chart listed in the cache exists in `download_dir`, matches
+/// the checksum recorded at download time and is a valid PDF
+///
+/// Charts without a recorded checksum get one if their file is a valid PDF.
+/// Stops early on cancel request. `on_progress` is called with the number of
+/// charts checked and the total.
+pub fn verify<F>(
+    database: &VacDatabase,
+    cache: &CacheDatabase,
+    download_dir: &Path,
+    cancel: &AtomicBool,
+    mut on_progress: F,
+) -> Result<VerifyReport>
+where
+    F: FnMut(usize, usize),
+{
+    let entries = database.get_all_entries()?;
+    let total = entries.len();
+    let mut report = VerifyReport::default();
+
+    for (idx, entry) in entries.into_iter().enumerate() {
+        on_progress(idx, total);
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let path = download_dir.join(&entry.file_name);
+        report.checked += 1;
+        if !path.exists() {
+            report.problems.push((entry.oaci, ChartProblem::Missing));
+            continue;
+        }
+
+        // A damaged PDF is reported as such rather than as a checksum mismatch
+        if let Err(problem) = check_pdf(&path) {
+            report.problems.push((entry.oaci, problem));
+            continue;
+        }
+
+        let hash = match file_hash(&path) {
+            Ok(hash) => hash,
+            Err(e) => {
+                report
+                    .problems
+                    .push((entry.oaci, ChartProblem::Invalid(e.to_string())));
+                continue;
+            }
+        };
+        match &entry.file_hash {
+            Some(stored) if *stored != hash => {
+                report.problems.push((entry.oaci, ChartProblem::Corrupted));
+            }
+            Some(_) => {}
+            None => {
+                cache.set_file_hash(&entry.oaci, &entry.vac_type, &hash)?;
+                report.recorded.push(entry.oaci);
+            }
+        }
+    }
+
+    on_progress(report.checked, total);
+    Ok(report)
+}
+
+/// Check the structure of a PDF file
+fn check_pdf(path: &Path) -> std::result::Result<(), ChartProblem> {
+    let invalid = |e: &dyn Display| ChartProblem::Invalid(e.to_string());
+
+    let mut file = fs::File::open(path).map_err(|e| invalid(&e))?;
+    let mut header = [0u8; 5];
+    if file.read_exact(&mut header).is_err() || &header != b"%PDF-" {
+        return Err(ChartProblem::Invalid("no PDF header".to_string()));
+    }
+
+    // A complete PDF ends with an end-of-file marker, possibly followed by a newline
+    let len = file.metadata().map_err(|e| invalid(&e))?.len();
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(len.saturating_sub(1024)))
+        .and_then(|_| file.read_to_end(&mut tail))
+        .map_err(|e| invalid(&e))?;
+    if !tail.windows(5).any(|w| w == b"%%EOF") {
+        return Err(ChartProblem::Truncated);
+    }
+
+    Document::load(path).map_err(|e| invalid(&e))?;
+    Ok(())
+}
+
+/// Version recorded for adopted charts, whose edition is unknown
+///
+/// It never matches a published version, so adopted charts are reported as
+/// outdated until they are updated.
+pub const UNKNOWN_VERSION: &str = "unknown";
+
+/// PDF file of the download directory that is not listed in the cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignFile {
+    pub file_name: String,
+    pub size: u64,
+    /// OACI code guessed from the file name, if any
+    pub oaci: Option<String>,
+}
+
+/// List the PDF files of `download_dir` that the cache does not know about
+///
+/// Each file is matched to a chart of `entries` by its file name, or by an
+/// OACI code found in it (e.g. "LFPN - Toussus.pdf").
+pub fn scan_foreign(
+    database: &VacDatabase,
+    download_dir: &Path,
+    entries: &[VacEntry],
+) -> Result<Vec<ForeignFile>> {
+    let known: HashSet<String> = database
+        .get_all_entries()?
+        .into_iter()
+        .map(|e| e.file_name)
+        .collect();
+
+    let mut files = Vec::new();
+    for dir_entry in fs::read_dir(download_dir)? {
+        let dir_entry = dir_entry?;
+        let file_name = dir_entry.file_name().to_string_lossy().to_string();
+        let is_pdf = Path::new(&file_name)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+        if !is_pdf || known.contains(&file_name) || !dir_entry.file_type()?.is_file() {
+            continue;
+        }
+
+        let oaci = entries
+            .iter()
+            .find(|e| e.file_name == file_name)
+            .or_else(|| {
+                file_name
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .find_map(|word| entries.iter().find(|e| e.oaci.eq_ignore_ascii_case(word)))
+            })
+            .map(|e| e.oaci.clone());
+        files.push(ForeignFile {
+            size: dir_entry.metadata()?.len(),
+            file_name,
+            oaci,
+        });
+    }
+
+    files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(files)
+}
+
+/// Add a foreign file to the cache as the local copy of `entry`
+///
+/// The file is renamed after the chart if needed. Its version is unknown, so
+/// the chart is reported as outdated.
+pub fn adopt(
+    database: &VacDatabase,
+    download_dir: &Path,
+    file_name: &str,
+    entry: &VacEntry,
+) -> Result<()> {
+    if database.has_entry(&entry.oaci)? {
+        return Err(format!("{} is already downloaded", entry.oaci).into());
+    }
+
+    let source = download_dir.join(file_name);
+    check_pdf(&source).map_err(|problem| format!("{}: {}", file_name, problem))?;
+
+    let dest = download_dir.join(&entry.file_name);
+    if source != dest {
+        if dest.exists() {
+            return Err(format!("{} already exists", entry.file_name).into());
+        }
+        fs::rename(&source, &dest)?;
+    }
+
+    let mut entry = entry.clone();
+    entry.version = UNKNOWN_VERSION.to_string();
+    entry.file_hash = Some(file_hash(&dest)?);
+    entry.file_size = fs::metadata(&dest)?.len() as i64;
+    database.upsert_entry(&entry)?;
+    Ok(())
+}
+
+/// Transfer the charts listed in the cache from `from` to `to`
+///
+/// Every chart is copied and the copy checked against the source before anything
+/// is changed: on error or cancel request, the copies are removed and the old
+/// directory and cache are left as they were. Files already in `to` are never
+/// replaced; the relocation fails if one differs from the chart of the same name.
+/// Once all charts are in place, the cache is updated. The old files are left in
+/// place: in [`RelocationMode::Move`], they are listed in the report for the
+/// caller to remove once the new directory is in use.
+///
+/// `on_progress` is called with the number of charts processed and the total.
+pub fn relocate<F>(
+    database: &VacDatabase,
+    cache: &CacheDatabase,
+    from: &Path,
+    to: &Path,
+    mode: RelocationMode,
+    cancel: &AtomicBool,
+    mut on_progress: F,
+) -> Result<RelocationReport>
+where
+    F: FnMut(usize, usize),
+{
+    fs::create_dir_all(to)?;
+    if fs::canonicalize(from).ok() == Some(fs::canonicalize(to)?) {
+        return Ok(RelocationReport::default());
+    }
+
+    let entries = database.get_all_entries()?;
+    let total = entries.len();
+
+    // Make sure the charts fit in the new directory before copying anything
+    let required: u64 = entries
+        .iter()
+        .filter_map(|entry| fs::metadata(from.join(&entry.file_name)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    match free_space(to) {
+        Some(available) if required > available => {
+            return Err(format!(
+                "Not enough disk space: {} needed, {} available",
+                format_bytes(required),
+                format_bytes(available)
+            )
+            .into());
+        }
+        Some(_) => {}
+        None => eprintln!("⚠️  Free disk space unknown, skipping the space check"),
+    }
+
+    let mut report = RelocationReport::default();
+    let mut created: Vec<PathBuf> = Vec::new();
+    let mut hashes = Vec::new();
+
+    for (idx, entry) in entries.iter().enumerate() {
+        on_progress(idx, total);
+
+        if cancel.load(Ordering::SeqCst) {
+            rollback(&created);
+            return Err("Relocation cancelled, the charts were left in place".into());
+        }
+
+        let source = from.join(&entry.file_name);
+        if !source.exists() {
+            report.missing.push(entry.oaci.clone());
+            continue;
+        }
+
+        match copy_verified(entry, &source, to) {
+            Ok((hash, dest)) => {
+                if let Some(dest) = dest {
+                    created.push(dest);
+                }
+                hashes.push((entry, hash));
+                report.transferred += 1;
+            }
+            Err(e) => {
+                rollback(&created);
+                return Err(format!("Failed to relocate {}: {}", entry.oaci, e).into());
+            }
+        }
+    }
+    on_progress(total, total);
+
+    // All charts are in place, update the cache
+    for oaci in &report.missing {
+        println!("  ⚠️  File missing for {} - removed from cache", oaci);
+        database.delete_entry(oaci)?;
+    }
+    for (entry, hash) in hashes {
+        if entry.file_hash.is_none() {
+            cache.set_file_hash(&entry.oaci, &entry.vac_type, &hash)?;
+        }
+    }
+
+    if mode == RelocationMode::Move {
+        report.sources = entries
+            .iter()
+            .map(|entry| from.join(&entry.file_name))
+            .filter(|source| source.exists())
+            .collect();
+    }
+
+    Ok(report)
+}
+
+/// Copy a chart to `to` and check that the copy is identical to the source
+///
+/// Returns the hash and the path of the new file, or `None` when an identical
+/// file was already present. A different file of the same name is an error.
+fn copy_verified(
+    entry: &vac_downloader::VacEntry,
+    source: &Path,
+    to: &Path,
+) -> Result<(String, Option<PathBuf>)> {
+    let expected = file_hash(source)?;
+
+    let dest = to.join(&entry.file_name);
+    if dest.exists() {
+        if file_hash(&dest)? == expected {
+            return Ok((expected, None));
+        }
+        return Err(format!(
+            "a different file named {} is already in the new directory",
+            entry.file_name
+        )
+        .into());
+    }
+
+    let part = to.join(format!("{}.part", entry.file_name));
+    if let Err(e) = fs::copy(source, &part) {
+        let _ = fs::remove_file(&part);
+        return Err(e.into());
+    }
+    let hash = file_hash(&part)?;
+    if hash != expected {
+        let _ = fs::remove_file(&part);
+        return Err(format!("hash mismatch after copy ({} != {})", hash, expected).into());
+    }
+    if let Err(e) = fs::rename(&part, &dest) {
+        let _ = fs::remove_file(&part);
+        return Err(e.into());
+    }
+
+    Ok((hash, Some(dest)))
+}
+
+/// Remove the files copied by an aborted relocation
+fn rollback(created: &[PathBuf]) {
+    for path in created {
+        if let Err(e) = fs::remove_file(path) {
+            eprintln!("  ✗ Failed to remove {:?}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DATABASE_NAME, library};
+    use tempfile::TempDir;
+
+    fn cache(dir: &TempDir) -> CacheDatabase {
+        CacheDatabase::open(dir.path().join(DATABASE_NAME)).unwrap()
+    }
+
+    #[test]
+    fn relocate_copies_and_leaves_sources_until_removed() {
+        let (dir, database, from) = library(&["LFPN", "LFRS"]);
+        let to = dir.path().join("to");
+
+        let report = relocate(
+            &database,
+            &cache(&dir),
+            &from,
+            &to,
+            RelocationMode::Move,
+            &AtomicBool::new(false),
+            |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(report.transferred, 2);
+        assert_eq!(fs::read_to_string(to.join("LFPN.pdf")).unwrap(), "LFPN");
+        assert!(from.join("LFPN.pdf").exists());
+        assert_eq!(report.sources.len(), 2);
+
+        report.remove_sources();
+        assert!(!from.join("LFPN.pdf").exists());
+        assert!(to.join("LFRS.pdf").exists());
+        assert!(database.get_cached_hash("LFPN", "AD").unwrap().is_some());
+    }
+
+    #[test]
+    fn relocate_keeps_a_different_existing_file_and_rolls_back() {
+        let (dir, database, from) = library(&["LFPN", "LFRS"]);
+        let to = dir.path().join("to");
+        fs::create_dir_all(&to).unwrap();
+        fs::write(to.join("LFRS.pdf"), "someone else's file").unwrap();
+
+        let result = relocate(
+            &database,
+            &cache(&dir),
+            &from,
+            &to,
+            RelocationMode::Move,
+            &AtomicBool::new(false),
+            |_, _| {},
+        );
+
+        assert!(result.is_err());
+        // The chart copied before the failure is removed, the other file is kept
+        assert!(!to.join("LFPN.pdf").exists());
+        assert_eq!(
+            fs::read_to_string(to.join("LFRS.pdf")).unwrap(),
+            "someone else's file"
+        );
+        assert!(from.join("LFPN.pdf").exists());
+        assert!(from.join("LFRS.pdf").exists());
+        assert!(database.get_cached_hash("LFPN", "AD").unwrap().is_none());
+    }
+
+    #[test]
+    fn relocate_accepts_an_identical_existing_file() {
+        let (dir, database, from) = library(&["LFPN"]);
+        let to = dir.path().join("to");
+        fs::create_dir_all(&to).unwrap();
+        fs::write(to.join("LFPN.pdf"), "LFPN").unwrap();
+
+        let report = relocate(
+            &database,
+            &cache(&dir),
+            &from,
+            &to,
+            RelocationMode::Copy,
+            &AtomicBool::new(false),
+            |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(report.transferred, 1);
+        assert!(report.sources.is_empty());
+    }
+
+    #[test]
+    fn relocate_drops_missing_charts_from_the_cache() {
+        let (dir, database, from) = library(&["LFPN", "LFRS"]);
+        let to = dir.path().join("to");
+        fs::remove_file(from.join("LFRS.pdf")).unwrap();
+
+        let report = relocate(
+            &database,
+            &cache(&dir),
+            &from,
+            &to,
+            RelocationMode::Copy,
+            &AtomicBool::new(false),
+            |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(report.missing, vec!["LFRS".to_string()]);
+        assert!(!database.has_entry("LFRS").unwrap());
+    }
+
+    fn minimal_pdf(path: &Path) {
+        use lopdf::{Object, dictionary};
+
+        let mut document = Document::with_version("1.5");
+        let pages = document.new_object_id();
+        let page = document.add_object(dictionary! {"Type" => "Page", "Parent" => pages});
+        document.objects.insert(
+            pages,
+            Object::Dictionary(
+                dictionary! {"Type" => "Pages", "Kids" => vec![page.into()], "Count" => 1},
+            ),
+        );
+        let catalog = document.add_object(dictionary! {"Type" => "Catalog", "Pages" => pages});
+        document.trailer.set("Root", catalog);
+        document.save(path).unwrap();
+    }
+
+    #[test]
+    fn verify_reports_problems_and_records_missing_hashes() {
+        let (dir, database, from) = library(&["LFPN", "LFRS", "LFRN"]);
+        let cache = cache(&dir);
+        minimal_pdf(&from.join("LFPN.pdf"));
+        fs::write(from.join("LFRS.pdf"), "%PDF-1.5\ntruncated").unwrap();
+        fs::remove_file(from.join("LFRN.pdf")).unwrap();
+        let dates = cache.download_dates().unwrap();
+
+        let report = verify(&database, &cache, &from, &AtomicBool::new(false), |_, _| {}).unwrap();
+
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.recorded, vec!["LFPN".to_string()]);
+        assert!(
+            report
+                .problems
+                .contains(&("LFRS".to_string(), ChartProblem::Truncated))
+        );
+        assert!(
+            report
+                .problems
+                .contains(&("LFRN".to_string(), ChartProblem::Missing))
+        );
+        assert_eq!(cache.download_dates().unwrap(), dates);
+
+        // Once recorded, a changed file is reported as corrupted
+        let mut bytes = fs::read(from.join("LFPN.pdf")).unwrap();
+        bytes.extend_from_slice(b"\n% changed\n");
+        fs::write(from.join("LFPN.pdf"), bytes).unwrap();
+        let report = verify(&database, &cache, &from, &AtomicBool::new(false), |_, _| {}).unwrap();
+        assert!(
+            report
+                .problems
+                .contains(&("LFPN".to_string(), ChartProblem::Corrupted))
+        );
+    }
+}
@@ -20,27 +20,41 @@
  */
 
 mod app;
-mod config;
-mod models;
+mod cli;
 
+use clap::Parser;
 use eframe::egui;
+use vac_downloader_gui::ui_state::UiState;
 
 fn main() -> eframe::Result<()> {
+    // Run headless when a subcommand is given
+    let args = cli::Args::parse();
+    if let Some(command) = args.command {
+        std::process::exit(cli::run(command, args.json));
+    }
+
     // Load application icon
     let icon_data = load_icon();
 
+    // Restore the window geometry of the previous session
+    let ui_state = UiState::load();
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(ui_state.window_size.unwrap_or([1000.0, 700.0]))
+        .with_min_inner_size([800.0, 600.0])
+        .with_icon(icon_data);
+    if let Some(position) = ui_state.window_position {
+        viewport = viewport.with_position(position);
+    }
+
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1000.0, 700.0])
-            .with_min_inner_size([800.0, 600.0])
-            .with_icon(icon_data),
+        viewport,
         ..Default::default()
     };
 
     eframe::run_native(
         "VAC Downloader",
         options,
-        Box::new(|cc| Ok(Box::new(app::VacDownloaderApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::VacDownloaderApp::new(cc, ui_state)))),
     )
 }
 
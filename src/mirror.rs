@@ -0,0 +1,336 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use crate::engine::file_hash;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use vac_downloader::VacDatabase;
+
+/// File written in each mirror target to remember the charts it received
+///
+/// Only files listed there are ever replaced or removed in a target, so a
+/// mirror can safely point to a folder that also holds other documents.
+pub const STATE_FILE_NAME: &str = ".vac-mirror.toml";
+
+/// Charts copied to a mirror target, with the hash of the copied file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MirrorState {
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+}
+
+impl MirrorState {
+    fn load(target: &Path) -> Self {
+        fs::read_to_string(target.join(STATE_FILE_NAME))
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, target: &Path) -> Result<()> {
+        fs::write(target.join(STATE_FILE_NAME), toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Change needed to bring a mirror target in line with the library
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirrorAction {
+    /// Copy a chart missing from the target
+    Add(String),
+    /// Replace a chart whose content changed
+    Update(String),
+    /// Remove a chart deleted from the library
+    Remove(String),
+    /// Record a chart already in the target with the same content
+    Keep(String),
+    /// Leave alone a different file that has the name of a chart but was not
+    /// copied by the mirror
+    Conflict(String),
+}
+
+impl MirrorAction {
+    /// Whether the action copies or removes a file
+    pub fn is_change(&self) -> bool {
+        matches!(
+            self,
+            MirrorAction::Add(_) | MirrorAction::Update(_) | MirrorAction::Remove(_)
+        )
+    }
+}
+
+impl Display for MirrorAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MirrorAction::Add(file) => write!(f, "+ {}", file),
+            MirrorAction::Update(file) => write!(f, "~ {}", file),
+            MirrorAction::Remove(file) => write!(f, "- {}", file),
+            MirrorAction::Keep(file) => write!(f, "= {}", file),
+            MirrorAction::Conflict(file) => {
+                write!(f, "! {} (another file with this name is kept)", file)
+            }
+        }
+    }
+}
+
+/// Changes planned for one mirror target
+#[derive(Debug, Clone)]
+pub struct MirrorPlan {
+    pub target: PathBuf,
+    /// Whether the target folder exists; a missing one (e.g. an unmounted
+    /// drive) is skipped rather than created
+    pub found: bool,
+    pub actions: Vec<MirrorAction>,
+    /// Hash of each library chart, by file name
+    hashes: BTreeMap<String, String>,
+}
+
+impl MirrorPlan {
+    /// Compare the library with a mirror target, without changing anything
+    pub fn new(database: &VacDatabase, download_dir: &Path, target: &Path) -> Result<Self> {
+        if !target.is_dir() {
+            return Ok(Self {
+                target: target.to_path_buf(),
+                found: false,
+                actions: Vec::new(),
+                hashes: BTreeMap::new(),
+            });
+        }
+
+        let state = MirrorState::load(target);
+        let mut hashes = BTreeMap::new();
+
+        for entry in database.get_all_entries()? {
+            let source = download_dir.join(&entry.file_name);
+            if !source.exists() {
+                continue;
+            }
+            let hash = match entry.file_hash {
+                Some(hash) => hash,
+                None => file_hash(&source)?,
+            };
+            hashes.insert(entry.file_name, hash);
+        }
+
+        let mut actions = Vec::new();
+        for (file_name, hash) in &hashes {
+            let path = target.join(file_name);
+            match state.files.get(file_name) {
+                _ if !path.exists() => actions.push(MirrorAction::Add(file_name.clone())),
+                Some(mirrored) if mirrored == hash => {}
+                Some(_) => actions.push(MirrorAction::Update(file_name.clone())),
+                // Only files copied by the mirror are replaced
+                None if file_hash(&path).ok().as_ref() == Some(hash) => {
+                    actions.push(MirrorAction::Keep(file_name.clone()))
+                }
+                None => actions.push(MirrorAction::Conflict(file_name.clone())),
+            }
+        }
+        for file_name in state.files.keys() {
+            if !hashes.contains_key(file_name) && target.join(file_name).exists() {
+                actions.push(MirrorAction::Remove(file_name.clone()));
+            }
+        }
+
+        Ok(Self {
+            target: target.to_path_buf(),
+            found: true,
+            actions,
+            hashes,
+        })
+    }
+
+    /// Number of files to copy or remove
+    pub fn changes(&self) -> usize {
+        self.actions.iter().filter(|a| a.is_change()).count()
+    }
+
+    /// One-line description of the plan, e.g. "/media/tablet: 3 change(s)"
+    pub fn summary(&self) -> String {
+        if self.found {
+            format!("{}: {} change(s)", self.target.display(), self.changes())
+        } else {
+            format!("{}: folder not found, skipped", self.target.display())
+        }
+    }
+
+    /// Apply the planned changes, copying charts from `download_dir`
+    ///
+    /// Returns the number of files copied or removed.
+    pub fn apply(&self, download_dir: &Path) -> Result<usize> {
+        if !self.target.is_dir() {
+            return Err(format!("Mirror folder {:?} not found", self.target).into());
+        }
+        let mut state = MirrorState::load(&self.target);
+
+        for action in &self.actions {
+            match action {
+                MirrorAction::Add(file_name) | MirrorAction::Update(file_name) => {
+                    let part = self.target.join(format!("{}.part", file_name));
+                    fs::copy(download_dir.join(file_name), &part)
+                        .and_then(|_| fs::rename(&part, self.target.join(file_name)))
+                        .map_err(|e| {
+                            let _ = fs::remove_file(&part);
+                            format!("Failed to copy {} to {:?}: {}", file_name, self.target, e)
+                        })?;
+                    state
+                        .files
+                        .insert(file_name.clone(), self.hashes[file_name].clone());
+                }
+                MirrorAction::Remove(file_name) => {
+                    fs::remove_file(self.target.join(file_name)).map_err(|e| {
+                        format!(
+                            "Failed to remove {} from {:?}: {}",
+                            file_name, self.target, e
+                        )
+                    })?;
+                    state.files.remove(file_name);
+                }
+                MirrorAction::Keep(file_name) => {
+                    state
+                        .files
+                        .insert(file_name.clone(), self.hashes[file_name].clone());
+                }
+                MirrorAction::Conflict(_) => continue,
+            }
+            // Record progress so that an interrupted sync resumes correctly
+            state.save(&self.target)?;
+        }
+
+        // Forget charts removed from the target by hand
+        state
+            .files
+            .retain(|file_name, _| self.target.join(file_name).exists());
+        state.save(&self.target)?;
+
+        Ok(self.changes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::library;
+    use tempfile::TempDir;
+
+    /// Test library, and an empty mirror target next to it
+    fn setup(codes: &[&str]) -> (TempDir, VacDatabase, PathBuf, PathBuf) {
+        let (dir, database, downloads) = library(codes);
+        let target = dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+        (dir, database, downloads, target)
+    }
+
+    #[test]
+    fn copies_new_charts_once() {
+        let (_dir, database, downloads, target) = setup(&["LFPN", "LFRS"]);
+
+        let plan = MirrorPlan::new(&database, &downloads, &target).unwrap();
+        assert_eq!(
+            plan.actions,
+            vec![
+                MirrorAction::Add("LFPN.pdf".to_string()),
+                MirrorAction::Add("LFRS.pdf".to_string())
+            ]
+        );
+        assert_eq!(plan.apply(&downloads).unwrap(), 2);
+        assert_eq!(fs::read_to_string(target.join("LFPN.pdf")).unwrap(), "LFPN");
+
+        let plan = MirrorPlan::new(&database, &downloads, &target).unwrap();
+        assert_eq!(plan.changes(), 0);
+    }
+
+    #[test]
+    fn updates_and_removes_mirrored_charts() {
+        let (_dir, database, downloads, target) = setup(&["LFPN", "LFRS"]);
+        MirrorPlan::new(&database, &downloads, &target)
+            .unwrap()
+            .apply(&downloads)
+            .unwrap();
+
+        fs::write(downloads.join("LFPN.pdf"), "LFPN new edition").unwrap();
+        database.delete_entry("LFRS").unwrap();
+
+        let plan = MirrorPlan::new(&database, &downloads, &target).unwrap();
+        assert_eq!(
+            plan.actions,
+            vec![
+                MirrorAction::Update("LFPN.pdf".to_string()),
+                MirrorAction::Remove("LFRS.pdf".to_string())
+            ]
+        );
+        plan.apply(&downloads).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join("LFPN.pdf")).unwrap(),
+            "LFPN new edition"
+        );
+        assert!(!target.join("LFRS.pdf").exists());
+    }
+
+    #[test]
+    fn leaves_files_it_did_not_copy() {
+        let (_dir, database, downloads, target) = setup(&["LFPN", "LFRS"]);
+        fs::write(target.join("LFPN.pdf"), "LFPN").unwrap();
+        fs::write(target.join("LFRS.pdf"), "annotated copy").unwrap();
+        fs::write(target.join("notes.pdf"), "notes").unwrap();
+
+        let plan = MirrorPlan::new(&database, &downloads, &target).unwrap();
+        assert_eq!(
+            plan.actions,
+            vec![
+                MirrorAction::Keep("LFPN.pdf".to_string()),
+                MirrorAction::Conflict("LFRS.pdf".to_string())
+            ]
+        );
+        assert_eq!(plan.apply(&downloads).unwrap(), 0);
+        assert_eq!(
+            fs::read_to_string(target.join("LFRS.pdf")).unwrap(),
+            "annotated copy"
+        );
+        assert!(target.join("notes.pdf").exists());
+
+        // The identical chart is now mirrored, so it is removed with the library one
+        database.delete_entry("LFPN").unwrap();
+        database.delete_entry("LFRS").unwrap();
+        let plan = MirrorPlan::new(&database, &downloads, &target).unwrap();
+        assert_eq!(
+            plan.actions,
+            vec![MirrorAction::Remove("LFPN.pdf".to_string())]
+        );
+    }
+
+    #[test]
+    fn skips_a_missing_folder() {
+        let (_dir, database, downloads, target) = setup(&["LFPN"]);
+        let missing = target.join("unmounted");
+
+        let plan = MirrorPlan::new(&database, &downloads, &missing).unwrap();
+        assert!(!plan.found);
+        assert!(plan.actions.is_empty());
+        assert!(plan.summary().ends_with("folder not found, skipped"));
+        assert!(plan.apply(&downloads).is_err());
+        assert!(!missing.exists());
+    }
+}
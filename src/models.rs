@@ -21,6 +21,8 @@
 
 /// Wrapper around vac_downloader::VacEntry with UI-specific state
 use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 pub struct VacEntryWithSelection {
     /// The underlying VAC entry from the library
@@ -38,19 +40,272 @@ impl VacEntryWithSelection {
     }
 }
 
+/// Progress of a download operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadProgress {
+    /// Number of charts downloaded, or failed, so far
+    pub current: usize,
+    /// Number of charts to process, up to date ones included
+    pub total: usize,
+    /// Number of charts found up to date, which are not downloaded
+    pub up_to_date: usize,
+    /// OACI code of the chart being processed
+    pub oaci: Option<String>,
+    /// Bytes downloaded so far
+    pub bytes: u64,
+    /// When the first chart download started
+    pub started: Option<Instant>,
+}
+
+impl DownloadProgress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            current: 0,
+            total,
+            up_to_date: 0,
+            oaci: None,
+            bytes: 0,
+            started: None,
+        }
+    }
+
+    /// Number of charts processed so far, up to date ones included
+    pub fn done(&self) -> usize {
+        self.up_to_date + self.current
+    }
+
+    /// Number of charts to download
+    pub fn to_download(&self) -> usize {
+        self.total.saturating_sub(self.up_to_date)
+    }
+
+    /// Completed fraction, between 0 and 1
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done() as f32 / self.total as f32
+        }
+    }
+
+    /// Average download speed in bytes per second
+    pub fn throughput(&self) -> f64 {
+        let elapsed = self.elapsed();
+        if elapsed > 0.0 {
+            self.bytes as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Estimated time remaining, based on the average time per downloaded chart
+    ///
+    /// Charts found up to date take no time and are left out.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.current == 0 {
+            return None;
+        }
+        let per_chart = self.elapsed() / self.current as f64;
+        let remaining = self.to_download().saturating_sub(self.current);
+        Some(Duration::from_secs_f64(per_chart * remaining as f64))
+    }
+
+    /// Seconds since the first chart download started
+    fn elapsed(&self) -> f64 {
+        self.started
+            .map(|started| started.elapsed().as_secs_f64())
+            .unwrap_or(0.0)
+    }
+}
+
+impl Display for DownloadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.oaci {
+            Some(oaci) => write!(
+                f,
+                "Downloading {} ({} of {})",
+                oaci,
+                self.current + 1,
+                self.to_download()
+            )?,
+            None => write!(f, "Downloading {} of {}", self.done(), self.total)?,
+        }
+
+        if self.up_to_date > 0 {
+            write!(f, ", {} up to date", self.up_to_date)?;
+        }
+
+        if self.bytes > 0 {
+            write!(
+                f,
+                " - {} at {}/s",
+                format_bytes(self.bytes),
+                format_bytes(self.throughput() as u64)
+            )?;
+        }
+        if let Some(eta) = self.eta() {
+            write!(f, ", {} remaining", format_duration(eta))?;
+        }
+
+        write!(f, "...")
+    }
+}
+
+/// Outcome of a download operation, chart by chart
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DownloadReport {
+    /// Charts downloaded successfully
+    pub succeeded: Vec<String>,
+    /// Charts that needed retries, with the number of attempts made
+    pub retried: Vec<(String, u32)>,
+    /// Charts that could not be downloaded, with the reason
+    pub failed: Vec<(String, String)>,
+    /// Charts that were already up to date
+    pub up_to_date: usize,
+}
+
+impl DownloadReport {
+    /// OACI codes of the failed charts
+    pub fn failed_codes(&self) -> Vec<String> {
+        self.failed.iter().map(|(oaci, _)| oaci.clone()).collect()
+    }
+}
+
+/// Format a byte count for display (e.g. "1.5 MB")
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Format a duration for display (e.g. "2m 05s")
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Download stopped before it started because the charts may not fit on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpaceShortfall {
+    /// Bytes the download needs
+    pub required: u64,
+    /// Bytes free on the destination volume
+    pub available: u64,
+    /// Charts to download, or None for all of them
+    pub codes: Option<Vec<String>>,
+    /// Whether charts that look up to date are downloaded again
+    pub force: bool,
+}
+
+impl Display for SpaceShortfall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Not enough disk space: {} needed, {} available",
+            format_bytes(self.required),
+            format_bytes(self.available)
+        )
+    }
+}
+
+/// Outcome of deleting a local chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deletion {
+    pub oaci: String,
+    /// Whether the chart was in the library
+    pub found: bool,
+    /// Trash item holding the chart file, None when there was no file to keep
+    pub trash_id: Option<String>,
+}
+
+/// A downloaded chart and the path of its PDF, as used by the exports
+#[derive(Debug, Clone)]
+pub struct LocalChart {
+    pub oaci: String,
+    pub city: String,
+    pub version: String,
+    /// Path of the local chart PDF
+    pub path: PathBuf,
+}
+
 /// Application operation status
 #[derive(Debug, Clone, PartialEq)]
 pub enum OperationStatus {
     Idle,
     FetchingList,
-    Downloading { current: usize, total: usize },
+    Downloading(DownloadProgress),
     Deleting(String),
+    /// Checking local charts for newer versions
+    CheckingUpdates {
+        current: usize,
+        total: usize,
+    },
+    /// Transferring local charts to a new download directory
+    Relocating {
+        current: usize,
+        total: usize,
+    },
+    /// Checking the local charts for missing or damaged files
+    Verifying {
+        current: usize,
+        total: usize,
+    },
+    /// Writing an export file (briefing pack, archive...)
+    Exporting(String),
+    /// Copying changes to a mirror target
+    Mirroring(String),
+    /// Putting back a previous edition of a chart
+    Restoring(String),
+    /// Drawing two editions of a chart to compare them
+    Comparing(String),
+    /// Adding files found in the download directory to the cache
+    Adopting(String),
+    /// The operation was cancelled by the user
+    Cancelled {
+        completed: usize,
+        skipped: usize,
+    },
+    /// The operation finished with a message for the user
+    Completed(String),
     Error(String),
 }
 
 impl OperationStatus {
     pub fn is_busy(&self) -> bool {
-        !matches!(self, OperationStatus::Idle | OperationStatus::Error(_))
+        !matches!(
+            self,
+            OperationStatus::Idle
+                | OperationStatus::Cancelled { .. }
+                | OperationStatus::Completed(_)
+                | OperationStatus::Error(_)
+        )
+    }
+
+    /// Whether the operation can be stopped with a cancel request
+    pub fn is_cancellable(&self) -> bool {
+        matches!(
+            self,
+            OperationStatus::Downloading(_)
+                | OperationStatus::Deleting(_)
+                | OperationStatus::CheckingUpdates { .. }
+                | OperationStatus::Relocating { .. }
+                | OperationStatus::Verifying { .. }
+        )
     }
 }
 
@@ -59,13 +314,52 @@ impl Display for OperationStatus {
         let s = match self {
             OperationStatus::Idle => "Ready".to_string(),
             OperationStatus::FetchingList => "Fetching VAC list...".to_string(),
-            OperationStatus::Downloading { current, total } => {
-                format!("Downloading {} of {}...", current, total)
-            }
+            OperationStatus::Downloading(progress) => progress.to_string(),
             OperationStatus::Deleting(oaci) => format!("Deleting {}...", oaci),
+            OperationStatus::CheckingUpdates { current, total } => {
+                format!("Checking for updates {} of {}...", current, total)
+            }
+            OperationStatus::Relocating { current, total } => {
+                format!("Relocating charts {} of {}...", current, total)
+            }
+            OperationStatus::Verifying { current, total } => {
+                format!("Verifying charts {} of {}...", current, total)
+            }
+            OperationStatus::Exporting(what) => format!("Exporting {}...", what),
+            OperationStatus::Mirroring(target) => format!("Mirroring to {}...", target),
+            OperationStatus::Restoring(oaci) => format!("Restoring {}...", oaci),
+            OperationStatus::Comparing(oaci) => format!("Comparing {}...", oaci),
+            OperationStatus::Adopting(file_name) => format!("Adopting {}...", file_name),
+            OperationStatus::Cancelled { completed, skipped } => {
+                format!("Cancelled: {} completed, {} skipped", completed, skipped)
+            }
+            OperationStatus::Completed(msg) => msg.clone(),
             OperationStatus::Error(msg) => format!("Error: {}", msg),
         };
 
         write!(f, "{}", s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eta_leaves_out_charts_already_up_to_date() {
+        let mut progress = DownloadProgress::new(100);
+        progress.up_to_date = 90;
+        assert_eq!(progress.eta(), None);
+
+        progress.current = 2;
+        progress.started = Some(Instant::now() - Duration::from_secs(20));
+        let eta = progress.eta().unwrap().as_secs_f64();
+        assert!((79.0..=81.0).contains(&eta), "{}", eta);
+        assert_eq!(progress.fraction(), 0.92);
+        assert!(
+            progress
+                .to_string()
+                .starts_with("Downloading 92 of 100, 90 up to date, 1m ")
+        );
+    }
+}
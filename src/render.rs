@@ -0,0 +1,116 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Rasterization of chart pages with pdfium, used to compare the drawing of two
+//! editions.
+//!
+//! The pdfium library is loaded at run time, from the directory of the
+//! executable or else from the system libraries. Content pdfium cannot draw is
+//! reported with each page, so that a comparison never hides it.
+
+use crate::core::Result;
+use image::RgbaImage;
+use pdfium_render::prelude::*;
+use std::path::Path;
+
+/// Pixels per PDF point (1/72 inch) used by default, about 108 dpi
+pub const DEFAULT_SCALE: f32 = 1.5;
+
+/// Largest side of a rendered page, in pixels, to bound memory use
+const MAX_PAGE_SIDE: i32 = 4096;
+
+/// A page of a PDF file, drawn
+pub struct RenderedPage {
+    /// RGBA pixels of the page, or None if it could not be drawn at all
+    pub image: Option<RgbaImage>,
+    /// What could not be drawn on the page
+    pub skipped: Vec<String>,
+}
+
+/// Page renderer bound to the pdfium library
+pub struct Renderer {
+    pdfium: Pdfium,
+}
+
+impl Renderer {
+    /// Load pdfium, from the directory of the executable first
+    pub fn new() -> Result<Self> {
+        let local = std::env::current_exe().ok().and_then(|exe| {
+            exe.parent()
+                .map(Pdfium::pdfium_platform_library_name_at_path)
+        });
+        let bindings = match local.map(Pdfium::bind_to_library) {
+            Some(Ok(bindings)) => bindings,
+            _ => Pdfium::bind_to_system_library().map_err(|_| {
+                format!(
+                    "the pdfium library was not found, place {} next to the application or install it",
+                    Pdfium::pdfium_platform_library_name().to_string_lossy()
+                )
+            })?,
+        };
+
+        Ok(Self {
+            pdfium: Pdfium::new(bindings),
+        })
+    }
+
+    /// Draw every page of a PDF file, `scale` pixels per point
+    pub fn render_file(&self, path: &Path, scale: f32) -> Result<Vec<RenderedPage>> {
+        let document = self.pdfium.load_pdf_from_file(path, None)?;
+        let config = PdfRenderConfig::new()
+            .scale_page_by_factor(scale)
+            .set_maximum_width(MAX_PAGE_SIDE)
+            .set_maximum_height(MAX_PAGE_SIDE);
+
+        Ok(document
+            .pages()
+            .iter()
+            .map(|page| {
+                let mut skipped = Vec::new();
+                let unsupported = count_unsupported(page.objects().iter());
+                if unsupported > 0 {
+                    skipped.push(format!("{} object(s) of an unsupported type", unsupported));
+                }
+                let image = match page.render_with_config(&config) {
+                    Ok(bitmap) => Some(bitmap.as_image().to_rgba8()),
+                    Err(e) => {
+                        skipped.push(format!("the whole page ({})", e));
+                        None
+                    }
+                };
+                RenderedPage { image, skipped }
+            })
+            .collect())
+    }
+}
+
+/// Number of objects pdfium does not know how to draw, forms included
+fn count_unsupported<'a>(objects: impl Iterator<Item = PdfPageObject<'a>>) -> usize {
+    objects
+        .map(|object| match object.object_type() {
+            PdfPageObjectType::Unsupported => 1,
+            PdfPageObjectType::XObjectForm => object
+                .as_x_object_form_object()
+                .map_or(0, |form| count_unsupported(form.iter())),
+            _ => 0,
+        })
+        .sum()
+}
@@ -0,0 +1,97 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::models::VacEntryWithSelection;
+
+/// Aerodromes of a route, matched against the chart list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Route {
+    /// OACI codes with a chart, in route order
+    pub known: Vec<String>,
+    /// Codes that look like aerodromes but have no chart
+    pub unknown: Vec<String>,
+}
+
+impl Route {
+    /// Resolve route text against the chart list
+    ///
+    /// The text may be a list of OACI codes (`LFPN LFRS LFRN`) or a pasted
+    /// flight plan: every four-letter word is taken as an aerodrome, as well as
+    /// the first four letters of aerodrome and time groups such as `LFPN0900`.
+    /// Duplicates are ignored.
+    pub fn resolve(text: &str, entries: &[VacEntryWithSelection]) -> Self {
+        let mut route = Route::default();
+
+        for code in parse_codes(text) {
+            if route.known.contains(&code) || route.unknown.contains(&code) {
+                continue;
+            }
+            if entries.iter().any(|e| e.entry.oaci == code) {
+                route.known.push(code);
+            } else {
+                route.unknown.push(code);
+            }
+        }
+
+        route
+    }
+}
+
+/// Extract the candidate OACI codes of a route, in order
+fn parse_codes(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|word| {
+            let word = word.to_ascii_uppercase();
+            let (letters, rest) = word.split_at(word.len().min(4));
+            let is_code = letters.len() == 4 && letters.chars().all(|c| c.is_ascii_alphabetic());
+            let is_time =
+                rest.is_empty() || (rest.len() == 4 && rest.chars().all(|c| c.is_ascii_digit()));
+            (is_code && is_time).then(|| letters.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn chart(oaci: &str) -> VacEntryWithSelection {
+        VacEntryWithSelection::new(testing::entry(oaci))
+    }
+
+    #[test]
+    fn parses_codes_and_flight_plans() {
+        assert_eq!(parse_codes("lfpn, LFRS;LFRN"), ["LFPN", "LFRS", "LFRN"]);
+        assert_eq!(
+            parse_codes("(FPL-FGABC-VG -LFPN0900 -N0100F045 DCT -LFRS0130)"),
+            ["LFPN", "LFRS"]
+        );
+        assert!(parse_codes("DCT N0100F045 LF12 LFPNX").is_empty());
+    }
+
+    #[test]
+    fn resolves_against_the_chart_list() {
+        let route = Route::resolve("LFPN ZZZZ LFRS LFPN", &[chart("LFPN"), chart("LFRS")]);
+        assert_eq!(route.known, ["LFPN", "LFRS"]);
+        assert_eq!(route.unknown, ["ZZZZ"]);
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use vac_downloader::{VacDatabase, VacEntry};
+
+/// Disk usage of the local chart library
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageUsage {
+    /// Number of charts whose file exists
+    pub charts: usize,
+    /// Total size of the chart files, in bytes
+    pub total_size: u64,
+    /// Size of each chart file, by OACI code
+    pub sizes: HashMap<String, u64>,
+    /// Space available on the volume holding the download directory, if known
+    pub free_space: Option<u64>,
+}
+
+impl StorageUsage {
+    /// Measure the files of the charts listed in the cache
+    pub fn measure(database: &VacDatabase, download_dir: &Path) -> Result<Self> {
+        let mut sizes = HashMap::new();
+        for entry in database.get_all_entries()? {
+            if let Ok(metadata) = fs::metadata(download_dir.join(&entry.file_name)) {
+                sizes.insert(entry.oaci, metadata.len());
+            }
+        }
+
+        Ok(Self {
+            charts: sizes.len(),
+            total_size: sizes.values().sum(),
+            sizes,
+            free_space: free_space(download_dir),
+        })
+    }
+
+    /// Size of the local file of a chart, or its published size when not downloaded
+    pub fn file_size(&self, entry: &VacEntry) -> u64 {
+        self.sizes
+            .get(&entry.oaci)
+            .copied()
+            .unwrap_or(entry.file_size.max(0) as u64)
+    }
+
+    /// The `count` largest chart files, largest first
+    pub fn largest(&self, count: usize) -> Vec<(&str, u64)> {
+        let mut sizes: Vec<(&str, u64)> = self
+            .sizes
+            .iter()
+            .map(|(oaci, size)| (oaci.as_str(), *size))
+            .collect();
+        sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        sizes.truncate(count);
+        sizes
+    }
+}
+
+/// Space available to the user on the volume holding `path`
+///
+/// `path` does not have to exist yet: the nearest existing ancestor is used.
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::path::absolute(path).ok()?;
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let c_path = CString::new(existing.as_os_str().as_bytes()).ok()?;
+
+    // SAFETY: statvfs only writes to the zero-initialized struct it is given
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // The field types vary between platforms
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
+/// Space available to the user on the volume holding `path`
+///
+/// `path` does not have to exist yet: the nearest existing ancestor is used.
+#[cfg(windows)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let path = std::path::absolute(path).ok()?;
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let wide: Vec<u16> = existing
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut available = 0u64;
+    // SAFETY: the path is NUL-terminated and the unused outputs may be null
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Fixtures shared by the unit tests
+
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use vac_downloader::{VacDatabase, VacEntry};
+
+/// Name of the cache database in a [`library`] directory
+pub const DATABASE_NAME: &str = "cache.db";
+
+/// Cache entry of a chart stored as `<oaci>.pdf`
+pub fn entry(oaci: &str) -> VacEntry {
+    VacEntry {
+        oaci: oaci.to_string(),
+        city: "City".to_string(),
+        vac_type: "AD".to_string(),
+        version: "1".to_string(),
+        file_name: format!("{}.pdf", oaci),
+        file_size: 4,
+        file_hash: None,
+        available_locally: true,
+    }
+}
+
+/// Temporary library with one chart per code, each file holding its code
+///
+/// Returns the temporary directory, the cache database in it and the
+/// `downloads` directory holding the charts.
+pub fn library(codes: &[&str]) -> (TempDir, VacDatabase, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let database = VacDatabase::new(dir.path().join(DATABASE_NAME)).unwrap();
+    let downloads = dir.path().join("downloads");
+    fs::create_dir_all(&downloads).unwrap();
+    for code in codes {
+        database.upsert_entry(&entry(code)).unwrap();
+        fs::write(downloads.join(format!("{}.pdf", code)), code).unwrap();
+    }
+    (dir, database, downloads)
+}
@@ -0,0 +1,333 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::core::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use vac_downloader::{VacDatabase, VacEntry};
+
+/// File listing the charts in the trash directory
+pub const INDEX_FILE_NAME: &str = "trash.toml";
+
+/// A deleted chart kept in the trash, with what is needed to put it back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    /// Name of the file in the trash directory, unique
+    pub id: String,
+    pub oaci: String,
+    pub city: String,
+    pub vac_type: String,
+    pub version: String,
+    /// Name of the chart file in the download directory
+    pub file_name: String,
+    pub file_size: i64,
+    pub file_hash: Option<String>,
+    /// When the chart was deleted
+    pub deleted: DateTime<Utc>,
+}
+
+impl TrashItem {
+    /// Cache entry of the chart, as it was before deletion
+    fn entry(&self) -> VacEntry {
+        VacEntry {
+            oaci: self.oaci.clone(),
+            city: self.city.clone(),
+            vac_type: self.vac_type.clone(),
+            version: self.version.clone(),
+            file_name: self.file_name.clone(),
+            file_size: self.file_size,
+            file_hash: self.file_hash.clone(),
+            available_locally: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrashIndex {
+    #[serde(default)]
+    items: Vec<TrashItem>,
+}
+
+/// Deleted charts that can still be restored, oldest first
+///
+/// The index is read again before each change, so that several `Trash` values
+/// opened on the same directory never undo each other's changes.
+#[derive(Debug, Clone)]
+pub struct Trash {
+    dir: PathBuf,
+    items: Vec<TrashItem>,
+}
+
+impl Trash {
+    /// Load the trash index; a missing trash is empty
+    pub fn open(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            items: load_index(dir),
+        }
+    }
+
+    pub fn items(&self) -> &[TrashItem] {
+        &self.items
+    }
+
+    /// Move a chart file to the trash, returning the new item
+    pub fn put(&mut self, chart: &Path, entry: &VacEntry) -> Result<TrashItem> {
+        fs::create_dir_all(&self.dir)?;
+
+        let deleted = Utc::now();
+        let id = format!("{}_{}", deleted.format("%Y%m%d%H%M%S%3f"), entry.file_name);
+        move_file(chart, &self.dir.join(&id))?;
+
+        let item = TrashItem {
+            id,
+            oaci: entry.oaci.clone(),
+            city: entry.city.clone(),
+            vac_type: entry.vac_type.clone(),
+            version: entry.version.clone(),
+            file_name: entry.file_name.clone(),
+            file_size: entry.file_size,
+            file_hash: entry.file_hash.clone(),
+            deleted,
+        };
+        self.items = load_index(&self.dir);
+        self.items.push(item.clone());
+        self.save()?;
+        Ok(item)
+    }
+
+    /// Move a chart back to `download_dir` and add it to the cache again
+    ///
+    /// Fails if the chart was downloaded again since it was deleted.
+    pub fn restore(
+        &mut self,
+        id: &str,
+        database: &VacDatabase,
+        download_dir: &Path,
+    ) -> Result<TrashItem> {
+        self.items = load_index(&self.dir);
+        let item = self.items[self.position(id)?].clone();
+        if database.has_entry(&item.oaci)? {
+            return Err(format!("{} was downloaded again", item.oaci).into());
+        }
+
+        let dest = download_dir.join(&item.file_name);
+        if dest.exists() {
+            return Err(format!("{} already exists", dest.display()).into());
+        }
+        fs::create_dir_all(download_dir)?;
+        self.take_back(id, &dest)?;
+        database.upsert_entry(&item.entry())?;
+        Ok(item)
+    }
+
+    /// Move a chart file out of the trash to `dest`, e.g. when its deletion failed
+    pub fn take_back(&mut self, id: &str, dest: &Path) -> Result<()> {
+        self.items = load_index(&self.dir);
+        let idx = self.position(id)?;
+        move_file(&self.dir.join(id), dest)?;
+        self.items.remove(idx);
+        self.save()
+    }
+
+    /// Delete a chart from the trash for good
+    pub fn purge(&mut self, id: &str) -> Result<()> {
+        self.items = load_index(&self.dir);
+        let idx = self.position(id)?;
+        let path = self.dir.join(&self.items[idx].id);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        self.items.remove(idx);
+        self.save()
+    }
+
+    /// Delete the charts deleted more than `days` days ago, returning how many
+    pub fn purge_older_than(&mut self, days: u32) -> Result<usize> {
+        let limit = Utc::now() - Duration::days(days.into());
+        self.items = load_index(&self.dir);
+        let expired: Vec<String> = self
+            .items
+            .iter()
+            .filter(|item| item.deleted < limit)
+            .map(|item| item.id.clone())
+            .collect();
+
+        for id in &expired {
+            self.purge(id)?;
+        }
+        Ok(expired.len())
+    }
+
+    fn position(&self, id: &str) -> Result<usize> {
+        self.items
+            .iter()
+            .position(|item| item.id == id)
+            .ok_or_else(|| format!("No item {} in the trash", id).into())
+    }
+
+    fn save(&self) -> Result<()> {
+        let index = TrashIndex {
+            items: self.items.clone(),
+        };
+        fs::write(
+            self.dir.join(INDEX_FILE_NAME),
+            toml::to_string_pretty(&index)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Read the items of the trash index in `dir`; a missing index is empty
+fn load_index(dir: &Path) -> Vec<TrashItem> {
+    fs::read_to_string(dir.join(INDEX_FILE_NAME))
+        .ok()
+        .and_then(|contents| toml::from_str::<TrashIndex>(&contents).ok())
+        .map(|index| index.items)
+        .unwrap_or_default()
+}
+
+/// Move a file, copying it when source and destination are on different volumes
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if let Err(e) = fs::copy(from, to) {
+        let _ = fs::remove_file(to);
+        return Err(e.into());
+    }
+    fs::remove_file(from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{entry, library};
+
+    #[test]
+    fn put_and_restore() {
+        let (dir, database, downloads) = library(&["LFPN"]);
+        let trash_dir = dir.path().join("trash");
+        let mut trash = Trash::open(&trash_dir);
+
+        let item = trash
+            .put(&downloads.join("LFPN.pdf"), &entry("LFPN"))
+            .unwrap();
+        database.delete_entry("LFPN").unwrap();
+        assert!(!downloads.join("LFPN.pdf").exists());
+        assert!(trash_dir.join(&item.id).exists());
+        assert_eq!(Trash::open(&trash_dir).items().len(), 1);
+
+        trash.restore(&item.id, &database, &downloads).unwrap();
+        assert_eq!(
+            fs::read_to_string(downloads.join("LFPN.pdf")).unwrap(),
+            "LFPN"
+        );
+        assert!(database.has_entry("LFPN").unwrap());
+        assert!(Trash::open(&trash_dir).items().is_empty());
+    }
+
+    #[test]
+    fn restore_refuses_a_chart_downloaded_again() {
+        let (dir, database, downloads) = library(&["LFPN"]);
+        let mut trash = Trash::open(&dir.path().join("trash"));
+        let item = trash
+            .put(&downloads.join("LFPN.pdf"), &entry("LFPN"))
+            .unwrap();
+
+        assert!(trash.restore(&item.id, &database, &downloads).is_err());
+        assert_eq!(trash.items().len(), 1);
+    }
+
+    #[test]
+    fn take_back_moves_the_file_without_touching_the_cache() {
+        let (dir, database, downloads) = library(&["LFPN"]);
+        let mut trash = Trash::open(&dir.path().join("trash"));
+        let item = trash
+            .put(&downloads.join("LFPN.pdf"), &entry("LFPN"))
+            .unwrap();
+        database.delete_entry("LFPN").unwrap();
+
+        trash
+            .take_back(&item.id, &downloads.join("LFPN.pdf"))
+            .unwrap();
+        assert!(downloads.join("LFPN.pdf").exists());
+        assert!(trash.items().is_empty());
+        assert!(!database.has_entry("LFPN").unwrap());
+    }
+
+    #[test]
+    fn purge_deletes_for_good() {
+        let (dir, _database, downloads) = library(&["LFPN"]);
+        let trash_dir = dir.path().join("trash");
+        let mut trash = Trash::open(&trash_dir);
+        let item = trash
+            .put(&downloads.join("LFPN.pdf"), &entry("LFPN"))
+            .unwrap();
+
+        trash.purge(&item.id).unwrap();
+        assert!(!trash_dir.join(&item.id).exists());
+        assert!(Trash::open(&trash_dir).items().is_empty());
+        assert!(trash.purge(&item.id).is_err());
+    }
+
+    #[test]
+    fn purge_older_than_keeps_recent_items() {
+        let (dir, _database, downloads) = library(&["LFPN", "LFRS"]);
+        let trash_dir = dir.path().join("trash");
+        let mut trash = Trash::open(&trash_dir);
+        let old = trash
+            .put(&downloads.join("LFPN.pdf"), &entry("LFPN"))
+            .unwrap();
+        trash
+            .put(&downloads.join("LFRS.pdf"), &entry("LFRS"))
+            .unwrap();
+
+        trash.items[0].deleted = Utc::now() - Duration::days(40);
+        trash.save().unwrap();
+
+        assert_eq!(trash.purge_older_than(30).unwrap(), 1);
+        assert!(!trash_dir.join(&old.id).exists());
+        assert_eq!(trash.items().len(), 1);
+        assert_eq!(trash.items()[0].oaci, "LFRS");
+    }
+
+    #[test]
+    fn changes_made_through_another_value_are_kept() {
+        let (dir, _database, downloads) = library(&["LFPN", "LFRS"]);
+        let trash_dir = dir.path().join("trash");
+        let mut first = Trash::open(&trash_dir);
+        let mut second = Trash::open(&trash_dir);
+
+        first
+            .put(&downloads.join("LFPN.pdf"), &entry("LFPN"))
+            .unwrap();
+        second
+            .put(&downloads.join("LFRS.pdf"), &entry("LFRS"))
+            .unwrap();
+
+        assert_eq!(Trash::open(&trash_dir).items().len(), 2);
+    }
+}
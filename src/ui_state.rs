@@ -0,0 +1,105 @@
+/*
+ * Copyright (c) 2025 Jeremie Corbier
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::archive::DEFAULT_NAME_TEMPLATE;
+use crate::collections::ChartView;
+use crate::config::Config;
+use crate::core::{SortColumn, StatusFilter};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// User interface state remembered across sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// Current sort column
+    pub sort_column: SortColumn,
+    /// Sort ascending or descending
+    pub sort_ascending: bool,
+    /// Search query for filtering the chart list
+    pub search_query: String,
+    /// Local status filter for the chart list
+    pub status_filter: StatusFilter,
+    /// OACI codes of the selected charts
+    pub selected: Vec<String>,
+    /// Route typed or pasted in the route box
+    pub route: String,
+    /// Favorites or collection shown in the chart table
+    pub view: ChartView,
+    /// Naming of the chart files in exported archives
+    pub export_name_template: String,
+    /// Inner size of the main window, in points
+    pub window_size: Option<[f32; 2]>,
+    /// Position of the main window on screen, in points
+    pub window_position: Option<[f32; 2]>,
+    /// Time of the last successful background sync
+    pub last_sync: Option<DateTime<Local>>,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            sort_column: SortColumn::Oaci,
+            sort_ascending: true,
+            search_query: String::new(),
+            status_filter: StatusFilter::All,
+            selected: Vec::new(),
+            route: String::new(),
+            view: ChartView::All,
+            export_name_template: DEFAULT_NAME_TEMPLATE.to_string(),
+            window_size: None,
+            window_position: None,
+            last_sync: None,
+        }
+    }
+}
+
+impl UiState {
+    /// Get the path to the state file, next to the configuration file
+    pub fn file_path() -> PathBuf {
+        Config::config_file_path().with_file_name("ui_state.toml")
+    }
+
+    /// Load the state from file, or use the default state if it can't be read
+    pub fn load() -> Self {
+        let path = Self::file_path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        match toml::from_str(&contents) {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("⚠️  Failed to parse UI state file: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Save the state to file
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let toml_string = toml::to_string_pretty(self)?;
+        fs::write(Self::file_path(), toml_string)?;
+        Ok(())
+    }
+}